@@ -0,0 +1,104 @@
+//! Compares the plate merging used for wall colliders with a greedy maximal rectangles cover
+//!
+//! Run with `cargo bench --bench wall_merging`.
+
+use std::collections::HashSet;
+
+use bevy::prelude::Rect;
+use bevy_ecs_ldtk::prelude::GridCoords;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+#[path = "../src/wall_merging.rs"]
+#[allow(dead_code)]
+mod wall_merging;
+
+use wall_merging::merge_wall_rects;
+
+/// Grows every rect right as far as possible, then up while the whole row fits
+fn greedy_wall_rects(walls: &HashSet<GridCoords>, width: i32, height: i32) -> Vec<Rect<i32>> {
+    let mut covered = HashSet::new();
+    let mut rects = Vec::new();
+    let free = |covered: &HashSet<GridCoords>, x, y| {
+        let cell = GridCoords { x, y };
+        x < width && y < height && walls.contains(&cell) && !covered.contains(&cell)
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            if !free(&covered, x, y) {
+                continue;
+            }
+
+            let mut right = x;
+            while free(&covered, right + 1, y) {
+                right += 1;
+            }
+
+            let mut top = y;
+            while (x..=right).all(|rx| free(&covered, rx, top + 1)) {
+                top += 1;
+            }
+
+            for cy in y..=top {
+                for cx in x..=right {
+                    covered.insert(GridCoords { x: cx, y: cy });
+                }
+            }
+
+            rects.push(Rect {
+                left: x,
+                right,
+                bottom: y,
+                top,
+            });
+        }
+    }
+
+    rects
+}
+
+/// Random walls, `density` is the chance of a cell being a wall
+fn random_walls(size: i32, density: f64) -> HashSet<GridCoords> {
+    let mut rng = StdRng::seed_from_u64(0x5eed);
+
+    (0..size)
+        .flat_map(|y| (0..size).map(move |x| GridCoords { x, y }))
+        .filter(|_| rng.gen_bool(density))
+        .collect()
+}
+
+fn bench_wall_merging(c: &mut Criterion) {
+    let mut group = c.benchmark_group("wall_merging");
+
+    for size in [32, 128, 512] {
+        for density in [0.2, 0.5, 0.8] {
+            let walls = random_walls(size, density);
+            let parameter = format!("{}x{}@{}", size, size, density);
+
+            println!(
+                "{}: {} cells, {} plate rects, {} greedy rects",
+                parameter,
+                walls.len(),
+                merge_wall_rects(&walls, size, size).len(),
+                greedy_wall_rects(&walls, size, size).len(),
+            );
+
+            group.bench_with_input(
+                BenchmarkId::new("plates", &parameter),
+                &walls,
+                |b, walls| b.iter(|| merge_wall_rects(walls, size, size)),
+            );
+            group.bench_with_input(
+                BenchmarkId::new("greedy", &parameter),
+                &walls,
+                |b, walls| b.iter(|| greedy_wall_rects(walls, size, size)),
+            );
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_wall_merging);
+criterion_main!(benches);
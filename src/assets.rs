@@ -0,0 +1,80 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{Asset, AssetLoader, BoxedFuture, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use serde::de::DeserializeOwned;
+
+/// Loads any deserializable asset from RON
+///
+/// Every asset type registers its own extension like `colliders.ron`,
+/// so the loaders don't clash on plain `.ron` files.
+pub struct RonAssetLoader<T> {
+    extensions: &'static [&'static str],
+    _asset: PhantomData<fn() -> T>,
+}
+
+impl<T> RonAssetLoader<T> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            _asset: PhantomData,
+        }
+    }
+}
+
+impl<T> AssetLoader for RonAssetLoader<T>
+where
+    T: TypeUuid + DeserializeOwned + Send + Sync + 'static,
+{
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let asset: T = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
+
+pub trait AddRonAsset {
+    fn add_ron_asset<T>(&mut self, extensions: &'static [&'static str]) -> &mut Self
+    where
+        T: TypeUuid + DeserializeOwned + Send + Sync + 'static;
+}
+
+impl AddRonAsset for App {
+    fn add_ron_asset<T>(&mut self, extensions: &'static [&'static str]) -> &mut Self
+    where
+        T: TypeUuid + DeserializeOwned + Send + Sync + 'static,
+    {
+        self.add_asset::<T>()
+            .add_asset_loader(RonAssetLoader::<T>::new(extensions))
+    }
+}
+
+/// Whether the asset of `handle` was loaded or reloaded since the events were last read
+///
+/// All the events are read, even after a match.
+pub fn asset_loaded<T: Asset>(events: &mut EventReader<AssetEvent<T>>, handle: &Handle<T>) -> bool {
+    events
+        .iter()
+        .filter(|event| {
+            matches!(
+                event,
+                AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed }
+                if changed == handle
+            )
+        })
+        .count()
+        > 0
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::Deserialize;
+
+use crate::plugins::SpriteSort;
+
+use super::{ColliderBundle, Interactable, Item, LdtkFields};
+
+/// Atlas index of the closed chest sprite
+pub const CHEST_CLOSED_SPRITE: usize = 90;
+/// Atlas index of the opened chest sprite
+pub const CHEST_OPENED_SPRITE: usize = 101;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Component)]
+pub enum Chest {
+    Closed,
+    Opened,
+}
+
+impl Default for Chest {
+    fn default() -> Self {
+        Self::Closed
+    }
+}
+
+/// Items lying in a chest, filled from the `content` field
+#[derive(Clone, Debug, Default, Component, Deref, DerefMut)]
+pub struct ChestContents(pub Vec<Item>);
+
+impl From<EntityInstance> for ChestContents {
+    fn from(entity_instance: EntityInstance) -> Self {
+        #[derive(Default, Deserialize)]
+        struct ChestFields {
+            content: Vec<Item>,
+        }
+
+        Self(entity_instance.fields_or_default::<ChestFields>().content)
+    }
+}
+
+#[derive(Clone, Default, Bundle, LdtkEntity)]
+pub struct ChestBundle {
+    #[sprite_sheet_bundle("maps/verres-home/atlas.png", 16.0, 16.0, 11, 11, 0.0, 90)]
+    #[bundle]
+    pub sprite_bundle: SpriteSheetBundle,
+
+    #[from_entity_instance]
+    #[bundle]
+    pub collider_bundle: ColliderBundle,
+
+    pub chest: Chest,
+
+    #[from_entity_instance]
+    pub contents: ChestContents,
+
+    #[from_entity_instance]
+    pub interactable: Interactable,
+
+    #[from_entity_instance]
+    pub sort: SpriteSort,
+}
@@ -0,0 +1,73 @@
+use heron::prelude::*;
+
+use super::TileBehavior;
+
+/// Heron collision layers, every collider gets exactly one of them
+#[derive(Copy, Clone, Eq, PartialEq, Debug, PhysicsLayer)]
+pub enum CollisionLayer {
+    Player,
+    Mob,
+    /// Walls, water, doors and anything else that never moves
+    Wall,
+    /// Objects that can be pushed around, like chests
+    Item,
+    /// Sensors that only report who walks into them
+    Trigger,
+    Projectile,
+}
+
+/// Pairs of layers that collide or detect each other, layers not paired here pass through
+///
+/// This is the only place deciding who interacts with whom,
+/// the masks of every layer are built from it, so both sides always agree.
+const INTERACTIONS: [(CollisionLayer, CollisionLayer); 12] = [
+    (CollisionLayer::Player, CollisionLayer::Mob),
+    (CollisionLayer::Player, CollisionLayer::Wall),
+    (CollisionLayer::Player, CollisionLayer::Item),
+    (CollisionLayer::Player, CollisionLayer::Trigger),
+    (CollisionLayer::Player, CollisionLayer::Projectile),
+    (CollisionLayer::Mob, CollisionLayer::Mob),
+    (CollisionLayer::Mob, CollisionLayer::Wall),
+    (CollisionLayer::Mob, CollisionLayer::Projectile),
+    (CollisionLayer::Wall, CollisionLayer::Item),
+    (CollisionLayer::Wall, CollisionLayer::Projectile),
+    (CollisionLayer::Item, CollisionLayer::Item),
+    (CollisionLayer::Item, CollisionLayer::Projectile),
+];
+
+impl CollisionLayer {
+    /// Layer of an LDtk entity, by its identifier
+    pub fn for_entity(identifier: &str) -> Option<Self> {
+        match identifier {
+            "Player" => Some(Self::Player),
+            "Mob" => Some(Self::Mob),
+            "Chest" => Some(Self::Item),
+            "Door" => Some(Self::Wall),
+            "Transition" => Some(Self::Trigger),
+            _ => None,
+        }
+    }
+
+    /// Layer of the merged colliders of special tiles
+    pub fn for_tile(behavior: TileBehavior) -> Self {
+        match behavior {
+            TileBehavior::Water => Self::Wall,
+            TileBehavior::Slow | TileBehavior::Hazard | TileBehavior::Trigger => Self::Trigger,
+        }
+    }
+
+    /// The layer as the group, and every layer it interacts with as the masks
+    pub fn collision_layers(self) -> CollisionLayers {
+        let masks = INTERACTIONS.iter().filter_map(|&(a, b)| {
+            if a == self {
+                Some(b)
+            } else if b == self {
+                Some(a)
+            } else {
+                None
+            }
+        });
+
+        CollisionLayers::none().with_group(self).with_masks(masks)
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::Deserialize;
+
+use crate::plugins::SpriteSort;
+
+use super::{ColliderBundle, ColliderDefKey, Interactable, Item, LdtkFields};
+
+/// Index of the closed door sprite in the door atlas
+pub const DOOR_CLOSED_SPRITE: usize = 0;
+/// Index of the opened door sprite in the door atlas
+pub const DOOR_OPENED_SPRITE: usize = 1;
+
+/// Door frames are two tiles of `maps/verres-home/atlas.png` high: the panel and the dark doorway
+const DOOR_FRAMES: [Vec2; 2] = [Vec2::new(80., 0.), Vec2::new(96., 48.)];
+const DOOR_FRAME_SIZE: Vec2 = Vec2::new(16., 32.);
+
+#[derive(Clone, Debug, Component)]
+pub struct Door {
+    pub open: bool,
+    pub locked: bool,
+    /// Item the player needs to unlock the door
+    pub key: Item,
+    pub size: Vec2,
+}
+
+impl Default for Door {
+    fn default() -> Self {
+        Self {
+            open: false,
+            locked: false,
+            key: Item::Key,
+            size: Vec2::ZERO,
+        }
+    }
+}
+
+impl From<EntityInstance> for Door {
+    fn from(entity_instance: EntityInstance) -> Self {
+        #[derive(Default, Deserialize)]
+        struct DoorFields {
+            locked: bool,
+            #[serde(default)]
+            key: Option<Item>,
+        }
+
+        let DoorFields { locked, key } = entity_instance.fields_or_default();
+        Door {
+            locked,
+            key: key.unwrap_or(Item::Key),
+            size: IVec2::new(entity_instance.width, entity_instance.height).as_vec2(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Door {
+    /// Brings the collider of `entities.colliders.ron` back when the door is closed
+    pub fn collider_def_key(&self) -> ColliderDefKey {
+        ColliderDefKey {
+            identifier: "Door".to_owned(),
+            entity_size: self.size,
+        }
+    }
+}
+
+#[derive(Clone, Default, Bundle)]
+pub struct DoorBundle {
+    #[bundle]
+    pub sprite_bundle: SpriteSheetBundle,
+
+    #[bundle]
+    pub collider_bundle: ColliderBundle,
+
+    pub door: Door,
+
+    pub interactable: Interactable,
+
+    pub sort: SpriteSort,
+}
+
+// Doors have no tile in LDtk and their frames are two tiles high,
+// so the atlas is cut here and the sprite is sized from the entity instead of the derive
+impl LdtkEntity for DoorBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        asset_server: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        let door = Door::from(entity_instance.clone());
+
+        let mut texture_atlas = TextureAtlas::new_empty(
+            asset_server.load("maps/verres-home/atlas.png"),
+            Vec2::new(176., 176.),
+        );
+        for min in DOOR_FRAMES {
+            texture_atlas.add_texture(bevy::sprite::Rect {
+                min,
+                max: min + DOOR_FRAME_SIZE,
+            });
+        }
+
+        DoorBundle {
+            sprite_bundle: SpriteSheetBundle {
+                sprite: TextureAtlasSprite {
+                    index: DOOR_CLOSED_SPRITE,
+                    custom_size: Some(door.size),
+                    ..Default::default()
+                },
+                texture_atlas: texture_atlases.add(texture_atlas),
+                ..Default::default()
+            },
+            collider_bundle: ColliderBundle::from(entity_instance.clone()),
+            sort: SpriteSort::from(entity_instance.clone()),
+            interactable: Interactable::from(entity_instance.clone()),
+            door,
+        }
+    }
+}
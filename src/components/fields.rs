@@ -0,0 +1,142 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{ldtk::Level, prelude::*};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{Map, Value};
+
+/// Error returned when field instances don't match the requested struct
+#[derive(Debug)]
+pub struct FieldsError {
+    /// Identifier of the entity or level the fields belong to
+    pub owner: String,
+    pub source: serde_json::Error,
+}
+
+impl fmt::Display for FieldsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid fields of `{}`: {}", self.owner, self.source)
+    }
+}
+
+impl std::error::Error for FieldsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Color field, LDtk colors are read as `[r, g, b, a]`
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(from = "[f32; 4]")]
+pub struct FieldColor(pub Color);
+
+impl From<[f32; 4]> for FieldColor {
+    fn from([r, g, b, a]: [f32; 4]) -> Self {
+        Self(Color::rgba(r, g, b, a))
+    }
+}
+
+impl From<FieldColor> for Color {
+    fn from(color: FieldColor) -> Self {
+        color.0
+    }
+}
+
+/// Converts field instances into a JSON object keyed by field identifiers
+///
+/// LDtk enums become strings, so they deserialize into unit variants of Rust enums
+/// (use `#[serde(rename = "...")]` for values like `Healing_Plant`).
+/// Points keep the glam `[x, y]` format and deserialize into `IVec2`.
+/// Null elements of number, string, enum and point arrays are dropped,
+/// null values are kept so `Option` fields work.
+pub fn fields_to_value(field_instances: &[FieldInstance]) -> Value {
+    fn json<T: serde::Serialize>(value: T) -> Value {
+        serde_json::to_value(value).unwrap_or(Value::Null)
+    }
+
+    fn array<T: serde::Serialize>(values: &[Option<T>]) -> Value {
+        Value::Array(values.iter().flatten().map(json).collect())
+    }
+
+    fn color(color: &Color) -> Value {
+        json(color.as_rgba_f32())
+    }
+
+    let fields = field_instances
+        .iter()
+        .map(|field_instance| {
+            let value = match &field_instance.value {
+                FieldValue::Int(value) => json(value),
+                FieldValue::Float(value) => json(value),
+                FieldValue::Bool(value) => json(value),
+                FieldValue::String(value)
+                | FieldValue::FilePath(value)
+                | FieldValue::Enum(value) => json(value),
+                FieldValue::Color(value) => color(value),
+                FieldValue::Tile(value) => json(value),
+                FieldValue::EntityRef(value) => json(value),
+                FieldValue::Point(value) => json(value),
+                FieldValue::Ints(values) => array(values),
+                FieldValue::Floats(values) => array(values),
+                FieldValue::Bools(values) => json(values),
+                FieldValue::Strings(values)
+                | FieldValue::FilePaths(values)
+                | FieldValue::Enums(values) => array(values),
+                FieldValue::Colors(values) => Value::Array(values.iter().map(color).collect()),
+                FieldValue::Tiles(values) => json(values),
+                FieldValue::EntityRefs(values) => json(values),
+                FieldValue::Points(values) => array(values),
+            };
+
+            (field_instance.identifier.clone(), value)
+        })
+        .collect::<Map<String, Value>>();
+
+    Value::Object(fields)
+}
+
+/// Deserializes field instances into `T`
+pub fn parse_fields<T: DeserializeOwned>(
+    owner: &str,
+    field_instances: &[FieldInstance],
+) -> Result<T, FieldsError> {
+    serde_json::from_value(fields_to_value(field_instances)).map_err(|source| FieldsError {
+        owner: owner.to_owned(),
+        source,
+    })
+}
+
+/// Typed access to the field instances of LDtk entities and levels
+///
+/// ```ignore
+/// #[derive(Deserialize)]
+/// struct DoorFields {
+///     locked: bool,
+///     key: Option<Item>,
+/// }
+///
+/// let fields: DoorFields = entity_instance.fields()?;
+/// ```
+pub trait LdtkFields {
+    fn fields<T: DeserializeOwned>(&self) -> Result<T, FieldsError>;
+
+    /// Same as [`LdtkFields::fields`], but logs the error and falls back to the default value
+    fn fields_or_default<T: DeserializeOwned + Default>(&self) -> T {
+        self.fields().unwrap_or_else(|error| {
+            error!("{}", error);
+            T::default()
+        })
+    }
+}
+
+impl LdtkFields for EntityInstance {
+    fn fields<T: DeserializeOwned>(&self) -> Result<T, FieldsError> {
+        parse_fields(&self.identifier, &self.field_instances)
+    }
+}
+
+impl LdtkFields for Level {
+    fn fields<T: DeserializeOwned>(&self) -> Result<T, FieldsError> {
+        parse_fields(&self.identifier, &self.field_instances)
+    }
+}
@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+
+#[derive(Copy, Clone, PartialEq, Debug, Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            current: 100.,
+            max: 100.,
+        }
+    }
+}
+
+impl Health {
+    pub fn damage(&mut self, amount: f32) {
+        self.current = (self.current - amount).max(0.);
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.
+    }
+}
@@ -0,0 +1,52 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+/// Something the player can use with the interact action, see `InteractionPlugin`
+#[derive(Clone, Debug, Default, Component)]
+pub struct Interactable {
+    /// Locale key of the text floating over it while the player faces it
+    pub prompt: String,
+    /// Wins over lower priorities in reach, closer ones win among equal priorities
+    pub priority: i32,
+    /// Disabled objects can't be used, like emptied chests
+    pub enabled: bool,
+}
+
+impl Interactable {
+    pub fn new(prompt: &str, priority: i32) -> Self {
+        Self {
+            prompt: prompt.to_owned(),
+            priority,
+            enabled: true,
+        }
+    }
+}
+
+impl From<EntityInstance> for Interactable {
+    fn from(entity_instance: EntityInstance) -> Self {
+        match entity_instance.identifier.as_ref() {
+            // chests stand in front of doors more often than the other way around
+            "Chest" => Interactable::new("interact.open_chest", 1),
+            "Door" => Interactable::new("interact.open_door", 0),
+            _ => Interactable::default(),
+        }
+    }
+}
+
+/// Whoever can use `Interactable`s, the player for now
+#[derive(Clone, Debug, Component)]
+pub struct Interactor {
+    /// Last direction the actor moved in, normalized
+    pub facing: Vec2,
+    /// Interactable in reach and in front of the actor
+    pub focus: Option<Entity>,
+}
+
+impl Default for Interactor {
+    fn default() -> Self {
+        Self {
+            facing: Vec2::new(0., -1.),
+            focus: None,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::Deserialize;
+use strum::Display;
+
+use super::LdtkFields;
+
+/// Mirror of the `Item` enum defined in LDtk
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Display, Deserialize)]
+pub enum Item {
+    Knife,
+    #[strum(serialize = "Healing_Plant")]
+    #[serde(rename = "Healing_Plant")]
+    HealingPlant,
+    Meat,
+    Boots,
+    Water,
+    Gem,
+    Key,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum InventoryChange {
+    Added(Item),
+    Removed(Item),
+}
+
+/// Items carried by the player, seeded from the `items` field
+///
+/// Changes made through [`Inventory::add`] and [`Inventory::remove`] are journaled
+/// and turned into `ItemAdded` / `ItemRemoved` events by the `InventoryPlugin`.
+#[derive(Clone, Debug, Default, Component)]
+pub struct Inventory {
+    items: Vec<Item>,
+    changes: Vec<InventoryChange>,
+}
+
+impl From<EntityInstance> for Inventory {
+    fn from(entity_instance: EntityInstance) -> Self {
+        #[derive(Default, Deserialize)]
+        struct PlayerFields {
+            items: Vec<Item>,
+        }
+
+        let PlayerFields { items } = entity_instance.fields_or_default();
+        Self {
+            items,
+            changes: Vec::new(),
+        }
+    }
+}
+
+impl Inventory {
+    pub fn add(&mut self, item: Item) {
+        self.items.push(item);
+        self.changes.push(InventoryChange::Added(item));
+    }
+
+    /// Removes one `item`, returns `false` if there was none
+    pub fn remove(&mut self, item: Item) -> bool {
+        match self.items.iter().rposition(|&i| i == item) {
+            Some(index) => {
+                self.items.remove(index);
+                self.changes.push(InventoryChange::Removed(item));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, item: Item) -> bool {
+        self.items.contains(&item)
+    }
+
+    pub fn count(&self, item: Item) -> usize {
+        self.items.iter().filter(|&&i| i == item).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Item> {
+        self.items.iter()
+    }
+
+    pub fn has_changes(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    /// Takes the changes made since the last call
+    pub fn drain_changes(&mut self) -> std::vec::Drain<'_, InventoryChange> {
+        self.changes.drain(..)
+    }
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::ldtk::Level;
+use serde::Deserialize;
+
+use super::{FieldColor, LdtkFields};
+
+/// Metadata of the selected level, filled from its LDtk fields
+#[derive(Clone, Debug)]
+pub struct LevelInfo {
+    pub iid: String,
+    pub identifier: String,
+    /// Locale key of the name shown to the player
+    pub name: String,
+    /// Path of the music track, relative to `assets`
+    pub music: Option<String>,
+    pub ambient: Color,
+    /// Mobs and hazards leave the player alone here
+    pub safe_zone: bool,
+}
+
+impl Default for LevelInfo {
+    fn default() -> Self {
+        Self {
+            iid: String::new(),
+            identifier: String::new(),
+            name: String::new(),
+            music: None,
+            ambient: Color::WHITE,
+            safe_zone: false,
+        }
+    }
+}
+
+impl From<&Level> for LevelInfo {
+    fn from(level: &Level) -> Self {
+        #[derive(Default, Deserialize)]
+        #[serde(default)]
+        struct LevelFields {
+            name: Option<String>,
+            music: Option<String>,
+            ambient: Option<FieldColor>,
+            safe_zone: bool,
+        }
+
+        let LevelFields {
+            name,
+            music,
+            ambient,
+            safe_zone,
+        } = level.fields_or_default();
+
+        LevelInfo {
+            iid: level.iid.clone(),
+            identifier: level.identifier.clone(),
+            // levels without a name show their identifier
+            name: name.unwrap_or_else(|| level.identifier.clone()),
+            music,
+            ambient: ambient.map_or(Color::WHITE, Color::from),
+            safe_zone,
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{prelude::*, utils::ldtk_pixel_coords_to_translation_pivoted};
+use bevy_inspector_egui::Inspectable;
+use serde::Deserialize;
+
+use crate::plugins::SpriteSort;
+
+use super::{ColliderBundle, Item, LdtkFields};
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct Mob;
+
+/// Columns of the mob sheet to walk through, starting with the resting one
+pub const MOB_WALK_CYCLE: [usize; 4] = [1, 2, 1, 0];
+const MOB_SHEET_COLUMNS: usize = 3;
+
+/// Walk cycle of a mob, played in the row of the sheet facing where the mob walks
+///
+/// The sheet is laid out like the player's: the rows face down, left, right and up.
+#[derive(Clone, Debug, Component)]
+pub struct MobAnimation {
+    pub timer: Timer,
+    /// Position in `MOB_WALK_CYCLE`
+    pub step: usize,
+}
+
+impl Default for MobAnimation {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(0.15, true),
+            step: 0,
+        }
+    }
+}
+
+impl MobAnimation {
+    /// First sheet index of the row facing `direction`, or of the row of `index` without one
+    pub fn row_start(direction: Vec2, index: usize) -> usize {
+        let row = if direction == Vec2::ZERO {
+            index / MOB_SHEET_COLUMNS
+        } else if direction.x.abs() >= direction.y.abs() {
+            if direction.x < 0. {
+                1
+            } else {
+                2
+            }
+        } else if direction.y < 0. {
+            0
+        } else {
+            3
+        };
+
+        row * MOB_SHEET_COLUMNS
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct MobFields {
+    loot: Vec<Item>,
+    patrol: Vec<IVec2>,
+    #[serde(default)]
+    patrol_loop: bool,
+    #[serde(default)]
+    patrol_wait: Option<f32>,
+}
+
+/// Items dropped by a mob, filled from the `loot` field
+#[derive(Clone, Debug, Default, Component, Deref, DerefMut)]
+pub struct MobLoot(pub Vec<Item>);
+
+impl From<EntityInstance> for MobLoot {
+    fn from(entity_instance: EntityInstance) -> Self {
+        Self(entity_instance.fields_or_default::<MobFields>().loot)
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Inspectable)]
+pub enum PatrolMode {
+    /// Walks the points back and forth: 0, 1, 2, 1, 0, ...
+    PingPong,
+    /// Walks from the last point straight to the first one: 0, 1, 2, 0, 1, ...
+    Loop,
+}
+
+impl Default for PatrolMode {
+    fn default() -> Self {
+        Self::PingPong
+    }
+}
+
+/// Route of a mob in level space
+///
+/// The first point is the spawn position of the mob, the rest come from the `patrol` field.
+#[derive(Clone, PartialEq, Debug, Component, Inspectable)]
+pub struct Patrol {
+    pub points: Vec<Vec2>,
+    pub index: usize,
+    pub forward: bool,
+    pub mode: PatrolMode,
+    /// Seconds to stand still at every point
+    pub wait: f32,
+    pub wait_left: f32,
+    pub speed: f32,
+}
+
+impl Default for Patrol {
+    fn default() -> Self {
+        Self {
+            points: Vec::new(),
+            index: 0,
+            forward: true,
+            mode: PatrolMode::default(),
+            wait: 0.5,
+            wait_left: 0.,
+            speed: 40.,
+        }
+    }
+}
+
+impl Patrol {
+    /// Switches to the next point of the route
+    pub fn advance(&mut self) {
+        let len = self.points.len();
+        if len < 2 {
+            return;
+        }
+
+        match self.mode {
+            PatrolMode::Loop => self.index = (self.index + 1) % len,
+            PatrolMode::PingPong => {
+                if (self.forward && self.index + 1 >= len) || (!self.forward && self.index == 0) {
+                    self.forward = !self.forward;
+                }
+
+                if self.forward {
+                    self.index += 1;
+                } else {
+                    self.index -= 1;
+                }
+            }
+        }
+    }
+}
+
+impl LdtkEntity for Patrol {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> Patrol {
+        let level_height = layer_instance.c_hei * layer_instance.grid_size;
+        let entity_size = IVec2::new(entity_instance.width, entity_instance.height);
+
+        let mut patrol = Patrol {
+            points: vec![ldtk_pixel_coords_to_translation_pivoted(
+                entity_instance.px,
+                level_height,
+                entity_size,
+                entity_instance.pivot,
+            )],
+            // the mob stands on point 0 already
+            index: 1,
+            ..Default::default()
+        };
+
+        let fields: MobFields = entity_instance.fields_or_default();
+
+        for point in fields.patrol {
+            // points are stored as grid coords, the mob walks to the pivot of the cell
+            let pixel_coords = (point.as_vec2() + entity_instance.pivot)
+                * Vec2::splat(layer_instance.grid_size as f32);
+
+            patrol.points.push(ldtk_pixel_coords_to_translation_pivoted(
+                pixel_coords.as_ivec2(),
+                level_height,
+                entity_size,
+                entity_instance.pivot,
+            ));
+        }
+
+        if fields.patrol_loop {
+            patrol.mode = PatrolMode::Loop;
+        }
+
+        if let Some(wait) = fields.patrol_wait {
+            patrol.wait = wait;
+        }
+
+        if patrol.points.len() < 2 {
+            patrol.index = 0;
+        }
+
+        patrol
+    }
+}
+
+#[derive(Clone, Default, Bundle, LdtkEntity)]
+pub struct MobBundle {
+    #[sprite_sheet_bundle("images/mob.png", 32.0, 32.0, 3, 4, 0.0, 1)]
+    #[bundle]
+    pub sprite_bundle: SpriteSheetBundle,
+
+    #[from_entity_instance]
+    #[bundle]
+    pub collider_bundle: ColliderBundle,
+
+    pub mob: Mob,
+    pub animation: MobAnimation,
+
+    #[from_entity_instance]
+    pub loot: MobLoot,
+
+    #[ldtk_entity]
+    pub patrol: Patrol,
+
+    #[from_entity_instance]
+    pub sort: SpriteSort,
+}
@@ -1,6 +1,30 @@
 #![allow(clippy::forget_non_drop)]
 
+mod chest;
+mod collision_layer;
+mod door;
+mod fields;
+mod health;
+mod interactable;
+mod item;
+mod level;
+mod mob;
+mod movement;
+mod tile;
+mod transition;
 mod wall;
+pub use chest::*;
+pub use collision_layer::*;
+pub use door::*;
+pub use fields::*;
+pub use health::*;
+pub use interactable::*;
+pub use item::*;
+pub use level::*;
+pub use mob::*;
+pub use movement::*;
+pub use tile::*;
+pub use transition::*;
 pub use wall::*;
 
 use bevy::prelude::*;
@@ -12,6 +36,16 @@ use crate::systems::{MovementController, PlayerAnimationTimer};
 
 use crate::plugins::SpriteSort;
 
+/// LDtk identifier used to look up the collider in `entities.colliders.ron`
+///
+/// Empty for colliders that are built in code.
+#[derive(Clone, Debug, Default, Component)]
+pub struct ColliderDefKey {
+    pub identifier: String,
+    /// Size of the LDtk entity, for shapes that follow it
+    pub entity_size: Vec2,
+}
+
 #[derive(Clone, Debug, Default, Bundle, LdtkIntCell)]
 pub struct ColliderBundle {
     pub collider: CollisionShape,
@@ -19,46 +53,24 @@ pub struct ColliderBundle {
     pub velocity: Velocity,
     pub rotation_constraints: RotationConstraints,
     pub physic_material: PhysicMaterial,
+    pub collision_layers: CollisionLayers,
+    pub def_key: ColliderDefKey,
 }
 
 impl From<EntityInstance> for ColliderBundle {
     fn from(entity_instance: EntityInstance) -> ColliderBundle {
-        let rotation_constraints = RotationConstraints::lock();
-
-        match entity_instance.identifier.as_ref() {
-            "Player" => ColliderBundle {
-                collider: CollisionShape::Cuboid {
-                    half_extends: Vec3::new(6., 14., 0.),
-                    border_radius: None,
-                },
-                rigid_body: RigidBody::Dynamic,
-                rotation_constraints,
-                ..Default::default()
-            },
-            "Mob" => ColliderBundle {
-                collider: CollisionShape::Cuboid {
-                    half_extends: Vec3::new(5., 5., 0.),
-                    border_radius: None,
-                },
-                rigid_body: RigidBody::KinematicVelocityBased,
-                rotation_constraints,
-                ..Default::default()
-            },
-            "Chest" => ColliderBundle {
-                collider: CollisionShape::Cuboid {
-                    half_extends: Vec3::new(8., 8., 0.),
-                    border_radius: None,
-                },
-                rigid_body: RigidBody::Dynamic,
-                rotation_constraints,
-                physic_material: PhysicMaterial {
-                    friction: 0.5,
-                    density: 15.0,
-                    ..Default::default()
-                },
-                ..Default::default()
+        let collision_layers = CollisionLayer::for_entity(&entity_instance.identifier)
+            .map(CollisionLayer::collision_layers)
+            .unwrap_or_default();
+
+        // the shape and the body are filled in by the ColliderPlugin
+        ColliderBundle {
+            collision_layers,
+            def_key: ColliderDefKey {
+                identifier: entity_instance.identifier.clone(),
+                entity_size: IVec2::new(entity_instance.width, entity_instance.height).as_vec2(),
             },
-            _ => ColliderBundle::default(),
+            ..Default::default()
         }
     }
 }
@@ -66,7 +78,7 @@ impl From<EntityInstance> for ColliderBundle {
 impl From<EntityInstance> for SpriteSort {
     fn from(entity_instance: EntityInstance) -> Self {
         match entity_instance.identifier.as_ref() {
-            "Player" => SpriteSort {
+            "Player" | "Chest" | "Mob" | "Door" => SpriteSort {
                 layer: 0,
                 z_index: 1,
                 y_sort: true,
@@ -76,26 +88,6 @@ impl From<EntityInstance> for SpriteSort {
     }
 }
 
-impl From<IntGridCell> for ColliderBundle {
-    fn from(cell: IntGridCell) -> ColliderBundle {
-        let rotation_constraints = RotationConstraints::lock();
-
-        if cell.value == 2 {
-            ColliderBundle {
-                collider: CollisionShape::Cuboid {
-                    half_extends: Vec3::new(8., 8., 0.),
-                    border_radius: None,
-                },
-                rigid_body: RigidBody::Sensor,
-                rotation_constraints,
-                ..Default::default()
-            }
-        } else {
-            ColliderBundle::default()
-        }
-    }
-}
-
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
 pub struct Player;
 
@@ -112,6 +104,13 @@ pub struct PlayerBundle {
     pub player: Player,
 
     pub controller: MovementController,
+    pub tuning: MovementTuning,
+    pub stamina: Stamina,
+    pub health: Health,
+    pub interactor: Interactor,
+    pub tile_contacts: TileContacts,
+    #[from_entity_instance]
+    pub inventory: Inventory,
     pub animator: PlayerAnimationTimer,
 
     #[from_entity_instance]
@@ -0,0 +1,49 @@
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_inspector_egui::Inspectable;
+use serde::Deserialize;
+
+/// How an entity speeds up, slows down and sprints, in pixels and seconds
+///
+/// The player gets its values from `assets/player.movement.ron`.
+#[derive(Copy, Clone, PartialEq, Debug, Component, Inspectable, Deserialize, TypeUuid)]
+#[serde(default)]
+#[uuid = "9d3c2a61-7f0e-4b85-a4d2-6e1b8c5f3a07"]
+pub struct MovementTuning {
+    pub max_speed: f32,
+    /// Speed gained per second while moving
+    pub acceleration: f32,
+    /// Speed lost per second without movement input
+    pub friction: f32,
+    /// Max speed is multiplied by it while sprinting
+    pub sprint_multiplier: f32,
+    pub max_stamina: f32,
+    /// Stamina used per second of sprinting
+    pub stamina_drain: f32,
+    /// Stamina regained per second when not sprinting
+    pub stamina_regen: f32,
+    /// Share of the stamina to regain before sprinting again, once it ran out
+    pub sprint_recovery: f32,
+}
+
+impl Default for MovementTuning {
+    fn default() -> Self {
+        Self {
+            max_speed: 96.,
+            acceleration: 900.,
+            friction: 1200.,
+            sprint_multiplier: 1.6,
+            max_stamina: 100.,
+            stamina_drain: 35.,
+            stamina_regen: 20.,
+            sprint_recovery: 0.3,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Default, Component, Inspectable)]
+pub struct Stamina {
+    pub current: f32,
+    pub sprinting: bool,
+    /// Ran out of stamina, can't sprint until `MovementTuning::sprint_recovery` is regained
+    pub exhausted: bool,
+}
@@ -0,0 +1,131 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use heron::prelude::*;
+
+use super::LevelWalls;
+
+/// Gameplay meaning of the IntGrid values of the wall layer, `1` is a plain wall
+pub const TILE_BEHAVIORS: [(i32, TileBehavior); 4] = [
+    (2, TileBehavior::Slow),
+    (3, TileBehavior::Water),
+    (4, TileBehavior::Hazard),
+    (5, TileBehavior::Trigger),
+];
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Component)]
+pub enum TileBehavior {
+    /// Slows down whoever walks through it
+    Slow,
+    /// Can't be walked through
+    Water,
+    /// Hurts whoever stands on it
+    Hazard,
+    /// Only sends enter and exit events
+    Trigger,
+}
+
+impl TileBehavior {
+    pub fn from_int_grid_value(value: i32) -> Option<Self> {
+        TILE_BEHAVIORS
+            .iter()
+            .find(|(tile_value, _)| *tile_value == value)
+            .map(|(_, behavior)| *behavior)
+    }
+
+    /// Water blocks like a wall, the rest only notices who walks in
+    pub fn rigid_body(self) -> RigidBody {
+        match self {
+            TileBehavior::Water => RigidBody::Static,
+            TileBehavior::Slow | TileBehavior::Hazard | TileBehavior::Trigger => RigidBody::Sensor,
+        }
+    }
+}
+
+/// A special tile cell, its collider is merged into the `LevelTiles` colliders of its level
+#[derive(Clone, Debug, Bundle)]
+pub struct TileBundle {
+    pub behavior: TileBehavior,
+}
+
+impl LdtkIntCell for TileBundle {
+    fn bundle_int_cell(int_grid_cell: IntGridCell, _: &LayerInstance) -> Self {
+        TileBundle {
+            // TileBundle is only registered for the values from TILE_BEHAVIORS
+            behavior: TileBehavior::from_int_grid_value(int_grid_cell.value)
+                .unwrap_or(TileBehavior::Trigger),
+        }
+    }
+}
+
+/// Marks the merged colliders spawned for the special tiles of a level
+///
+/// They carry the `TileBehavior` of their cells, so contacts are tracked on them.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct TileCollider;
+
+/// Special tile cells of a level by behavior, like `LevelWalls` for the walls
+///
+/// Lives on the level entity and is filled from the `TileBehavior` cells when the level spawns.
+#[derive(Clone, Debug, Default, Component)]
+pub struct LevelTiles {
+    pub cells: HashMap<TileBehavior, HashSet<GridCoords>>,
+    pub width: i32,
+    pub height: i32,
+    pub grid_size: i32,
+}
+
+impl LevelTiles {
+    /// Empty tiles sized like the wall layer of the level, which holds the special tiles too
+    pub fn for_level(level: &LdtkLevel, layer_identifier: &str) -> Result<Self, String> {
+        let LevelWalls {
+            width,
+            height,
+            grid_size,
+            ..
+        } = LevelWalls::for_level(level, layer_identifier)?;
+
+        Ok(Self {
+            cells: HashMap::new(),
+            width,
+            height,
+            grid_size,
+        })
+    }
+
+    pub fn insert(&mut self, behavior: TileBehavior, coords: GridCoords) {
+        self.cells.entry(behavior).or_default().insert(coords);
+    }
+}
+
+/// Special tiles something is standing on right now
+#[derive(Clone, Debug, Default, Component)]
+pub struct TileContacts {
+    tiles: HashMap<Entity, TileBehavior>,
+    /// Seconds until the next hazard damage
+    pub hazard_cooldown: f32,
+}
+
+impl TileContacts {
+    /// Returns `true` if no other touched tile has the same behavior
+    pub fn insert(&mut self, tile: Entity, behavior: TileBehavior) -> bool {
+        let first = !self.touches(behavior);
+        self.tiles.insert(tile, behavior);
+        first
+    }
+
+    /// Returns the behavior of the tile if no other touched tile has it
+    pub fn remove(&mut self, tile: Entity) -> Option<TileBehavior> {
+        let behavior = self.tiles.remove(&tile)?;
+        (!self.touches(behavior)).then(|| behavior)
+    }
+
+    pub fn touches(&self, behavior: TileBehavior) -> bool {
+        self.tiles.values().any(|&touched| touched == behavior)
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.tiles.keys().copied()
+    }
+}
@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{prelude::*, utils::ldtk_pixel_coords_to_translation_pivoted};
+use serde::Deserialize;
+
+use super::{ColliderBundle, LdtkFields};
+
+/// `Spawn` entity a transition leads to, from an LDtk entity reference
+#[derive(Clone, Debug, Deserialize)]
+pub struct TransitionTarget {
+    #[serde(rename = "entityIid")]
+    pub entity_iid: String,
+    #[serde(rename = "levelIid")]
+    pub level_iid: String,
+}
+
+impl TransitionTarget {
+    /// World position of the target spawn, read from the project so its level doesn't have to be loaded
+    pub fn world_position(&self, ldtk_asset: &LdtkAsset) -> Option<Vec2> {
+        let level = ldtk_asset
+            .project
+            .levels
+            .iter()
+            .find(|level| level.iid == self.level_iid)?;
+
+        let spawn = level
+            .layer_instances
+            .iter()
+            .flatten()
+            .flat_map(|layer| &layer.entity_instances)
+            .find(|entity| entity.iid == self.entity_iid)?;
+
+        let level_translation =
+            Vec2::new(level.world_x as f32, -(level.world_y + level.px_hei) as f32);
+
+        Some(
+            level_translation
+                + ldtk_pixel_coords_to_translation_pivoted(
+                    spawn.px,
+                    level.px_hei,
+                    IVec2::new(spawn.width, spawn.height),
+                    spawn.pivot,
+                ),
+        )
+    }
+}
+
+/// Moves the player stepping on it to the `Spawn` entity of its `target` field
+#[derive(Clone, Debug, Default, Component)]
+pub struct Transition {
+    pub target: Option<TransitionTarget>,
+}
+
+impl From<EntityInstance> for Transition {
+    fn from(entity_instance: EntityInstance) -> Self {
+        #[derive(Default, Deserialize)]
+        struct TransitionFields {
+            target: Option<TransitionTarget>,
+        }
+
+        let TransitionFields { target } = entity_instance.fields_or_default();
+        if target.is_none() {
+            warn!("Transition `{}` leads nowhere", entity_instance.iid);
+        }
+
+        Transition { target }
+    }
+}
+
+#[derive(Clone, Default, Bundle, LdtkEntity)]
+pub struct TransitionBundle {
+    #[from_entity_instance]
+    #[bundle]
+    pub collider_bundle: ColliderBundle,
+
+    #[from_entity_instance]
+    pub transition: Transition,
+}
@@ -1,6 +1,11 @@
+use std::collections::HashSet;
+
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 
+/// IntGrid value of wall cells
+pub const WALL_INT_GRID_VALUE: i32 = 1;
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
 pub struct Wall;
 
@@ -8,3 +13,85 @@ pub struct Wall;
 pub struct WallBundle {
     wall: Wall,
 }
+
+/// Which IntGrid layer holds the walls
+///
+/// Read when the int cells are registered, so it has to be inserted before the `TilePlugin`.
+#[derive(Clone, Debug)]
+pub struct WallSettings {
+    pub layer_identifier: String,
+}
+
+impl Default for WallSettings {
+    fn default() -> Self {
+        Self {
+            layer_identifier: "Collisions".to_owned(),
+        }
+    }
+}
+
+/// Marks the merged colliders spawned for the walls of a level
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct WallCollider;
+
+/// Wall cells of a level, the source of truth for its wall colliders
+///
+/// Lives on the level entity and is filled from the `Wall` tiles when the level spawns.
+/// Change it with `WallEdit` events to rebuild the colliders of the level.
+#[derive(Clone, Debug, Default, Component)]
+pub struct LevelWalls {
+    pub cells: HashSet<GridCoords>,
+    pub width: i32,
+    pub height: i32,
+    pub grid_size: i32,
+}
+
+impl LevelWalls {
+    /// Empty walls sized like the wall layer of the level
+    pub fn for_level(level: &LdtkLevel, layer_identifier: &str) -> Result<Self, String> {
+        let layer = level
+            .level
+            .layer_instances
+            .iter()
+            .flatten()
+            .find(|layer| layer.identifier == layer_identifier)
+            .ok_or_else(|| {
+                format!(
+                    "Level `{}` has no `{}` layer, its walls are ignored",
+                    level.level.identifier, layer_identifier
+                )
+            })?;
+
+        Ok(Self {
+            cells: HashSet::new(),
+            width: layer.c_wid,
+            height: layer.c_hei,
+            grid_size: layer.grid_size,
+        })
+    }
+
+    /// Whether the cell is inside of the level, walls or not
+    pub fn in_level(&self, coords: GridCoords) -> bool {
+        coords.x >= 0 && coords.y >= 0 && coords.x < self.width && coords.y < self.height
+    }
+
+    /// Cells outside of the level are left alone
+    pub fn set(&mut self, coords: GridCoords, is_wall: bool) {
+        if !self.in_level(coords) {
+            return;
+        }
+
+        if is_wall {
+            self.cells.insert(coords);
+        } else {
+            self.cells.remove(&coords);
+        }
+    }
+}
+
+/// Adds or removes a wall cell at runtime, e.g. for destructible walls or opened passages
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum WallEdit {
+    Add { level: Entity, coords: GridCoords },
+    Remove { level: Entity, coords: GridCoords },
+}
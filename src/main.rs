@@ -131,7 +131,11 @@ struct MenuUI {
 impl UI for MenuUI {
     fn bind<T: StateData>(state: T, app: &mut App) {
         app.add_system_set(SystemSet::on_enter(state.to_owned()).with_system(Self::setup))
-            .add_system_set(SystemSet::on_update(state.to_owned()).with_system(Self::update))
+            .add_system_set(
+                SystemSet::on_update(state.to_owned())
+                    .with_system(Self::update)
+                    .with_system(Self::navigate),
+            )
             .add_system_set(SystemSet::on_exit(state.to_owned()).with_system(Self::clean));
     }
 }
@@ -184,7 +188,10 @@ impl MenuUI {
             match *interaction {
                 Interaction::Clicked => {
                     *color = PRESSED_BUTTON.into();
-                    state.set(AppState::InGame).unwrap();
+                    // the keyboard may have started the game this frame already
+                    if let Err(error) = state.set(AppState::InGame) {
+                        warn!("Can't start the game: {:?}", error);
+                    }
                 }
                 Interaction::Hovered => {
                     *color = HOVERED_BUTTON.into();
@@ -196,17 +203,74 @@ impl MenuUI {
         }
     }
 
+    /// Moves the focus between buttons with the move actions, so the menu works without a mouse
+    fn navigate(
+        actions: Res<Actions>,
+        mut state: ResMut<State<AppState>>,
+        mut focus: Local<usize>,
+        mut button_query: Query<(Entity, &Interaction, &mut UiColor), With<Button>>,
+        added_query: Query<(), Added<Button>>,
+    ) {
+        let mut buttons: Vec<_> = button_query.iter_mut().collect();
+        if buttons.is_empty() {
+            return;
+        }
+        buttons.sort_by_key(|(entity, ..)| *entity);
+
+        let count = buttons.len();
+        let previous = *focus;
+        if actions.just_pressed(Action::MoveDown) {
+            *focus = (*focus + 1) % count;
+        }
+        if actions.just_pressed(Action::MoveUp) {
+            *focus = (*focus + count - 1) % count;
+        }
+        *focus = (*focus).min(count - 1);
+
+        if actions.just_pressed(Action::Confirm) {
+            // the menu has a single button for now, which starts the game
+            let (_, _, color) = &mut buttons[*focus];
+            **color = PRESSED_BUTTON.into();
+            if let Err(error) = state.set(AppState::InGame) {
+                warn!("Can't start the game: {:?}", error);
+            }
+            return;
+        }
+
+        if *focus != previous || !added_query.is_empty() {
+            for (index, (_, interaction, color)) in buttons.iter_mut().enumerate() {
+                if **interaction == Interaction::None {
+                    **color = if index == *focus {
+                        HOVERED_BUTTON
+                    } else {
+                        NORMAL_BUTTON
+                    }
+                    .into();
+                }
+            }
+        }
+    }
+
     fn clean(mut commands: Commands, menu_data: Res<Self>) {
         commands.entity(menu_data.button_entity).despawn_recursive();
     }
 }
 
+mod assets;
+mod pathfinding;
 mod plugins;
+mod wall_merging;
 
 use bevy::{ecs::schedule::StateData, prelude::*, window::WindowMode};
 use bevy_ecs_ldtk::prelude::*;
 use bevy_inspector_egui::{WorldInspectorParams, WorldInspectorPlugin};
-use plugins::{FpsMeterPlugin, FullscreenTogglePlugin, SpriteSortingPlugin};
+use plugins::{
+    Action, Actions, ActionsPlugin, CameraEffectsPlugin, CameraPlugin, ChestPlugin,
+    ClickToMovePlugin, ColliderPlugin, DoorPlugin, FpsMeterPlugin, FullscreenTogglePlugin,
+    InteractionPlugin, InventoryPlugin, LevelInfoPlugin, MobPlugin, MovementPlugin,
+    NavigationPlugin, SpriteSortingPlugin, TilePlugin, TransitionPlugin, TravelPlugin,
+    WorldMapPlugin,
+};
 
 use heron::prelude::*;
 
@@ -216,6 +280,9 @@ mod systems;
 fn main() {
     dotenv::dotenv().ok();
 
+    // walls are only taken from the configured layer, the TilePlugin reads it too
+    let wall_settings = components::WallSettings::default();
+
     App::new()
         .insert_resource(WindowDescriptor {
             width: 1270.0,
@@ -231,6 +298,10 @@ fn main() {
         .add_plugins(DefaultPlugins)
         .add_plugin(LdtkPlugin)
         .add_plugin(PhysicsPlugin::default())
+        .add_startup_system_to_stage(StartupStage::PreStartup, systems::watch_for_asset_changes)
+        .add_plugin(ActionsPlugin)
+        .add_plugin(MovementPlugin)
+        .add_plugin(ColliderPlugin)
         .add_plugin(FpsMeterPlugin)
         .add_plugin(FullscreenTogglePlugin)
         // inspector
@@ -250,23 +321,44 @@ fn main() {
             set_clear_color: SetClearColor::FromLevelBackground,
             ..Default::default()
         })
+        .insert_resource(wall_settings.clone())
         .add_state(AppState::Menu(MenuWindow::Main))
         .bind_ui::<MenuUI>(AppState::Menu(MenuWindow::Main))
         .add_plugin(SpriteSortingPlugin)
+        .add_plugin(InventoryPlugin)
+        .add_plugin(InteractionPlugin)
+        .add_plugin(ChestPlugin)
+        .add_plugin(MobPlugin)
+        .add_plugin(DoorPlugin)
+        .add_plugin(TilePlugin)
+        .add_plugin(TravelPlugin)
+        .add_plugin(TransitionPlugin)
+        .add_plugin(LevelInfoPlugin)
+        .add_plugin(CameraPlugin)
+        .add_plugin(CameraEffectsPlugin)
+        .add_plugin(WorldMapPlugin)
+        .add_plugin(NavigationPlugin)
+        .add_plugin(ClickToMovePlugin)
         .add_system_set(
             SystemSet::on_enter(AppState::Menu(MenuWindow::Main)).with_system(systems::setup),
         )
+        .add_system(systems::toggle_pause)
         .add_system_set(
             SystemSet::on_update(AppState::InGame)
                 .with_system(systems::pause_physics_during_load)
+                .with_system(systems::collect_walls)
+                .with_system(systems::edit_walls)
                 .with_system(systems::spawn_wall_collision)
                 .with_system(systems::read_player_input)
                 .with_system(systems::player_movement)
                 .with_system(systems::player_animation)
-                .with_system(systems::camera_fit_inside_current_level)
                 .with_system(systems::update_level_selection),
         )
-        .register_ldtk_int_cell::<components::WallBundle>(1)
+        .add_event::<components::WallEdit>()
+        .register_ldtk_int_cell_for_layer::<components::WallBundle>(
+            &wall_settings.layer_identifier,
+            components::WALL_INT_GRID_VALUE,
+        )
         .register_ldtk_entity::<components::PlayerBundle>("Player")
         .run();
 }
@@ -0,0 +1,347 @@
+//! A* search over grid cells
+//!
+//! Kept free of ECS types, like `wall_merging`, the grid is only seen through a `walkable` closure.
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use bevy::math::IVec2;
+
+/// Cost of a straight step, diagonal steps cost `DIAGONAL_COST`
+const STRAIGHT_COST: i32 = 10;
+const DIAGONAL_COST: i32 = 14;
+
+const DIRECTIONS: [(i32, i32); 8] = [
+    (1, 0),
+    (0, 1),
+    (-1, 0),
+    (0, -1),
+    (1, 1),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+];
+
+/// Octile distance, exact on an empty grid
+fn heuristic(from: IVec2, to: IVec2) -> i32 {
+    let delta = (to - from).abs();
+    let straight = (delta.x - delta.y).abs();
+    let diagonal = delta.x.min(delta.y);
+    straight * STRAIGHT_COST + diagonal * DIAGONAL_COST
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+struct OpenCell {
+    /// Estimated cost of the whole path through the cell
+    estimate: i32,
+    cost: i32,
+    cell: IVec2,
+}
+
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap, the cheapest estimate has to come out first
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.cost.cmp(&other.cost))
+            .then_with(|| (self.cell.x, self.cell.y).cmp(&(other.cell.x, other.cell.y)))
+    }
+}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// How a `Search` stands after a `Search::step`
+#[derive(Clone, Debug, PartialEq)]
+pub enum SearchState {
+    /// Out of budget, step again to continue
+    Searching,
+    /// Cells from the start to the goal, both included
+    Found(Vec<IVec2>),
+    NotFound,
+}
+
+/// A* search that can be spread over several frames
+///
+/// Moves in 8 directions, but never diagonally past a blocked cell,
+/// so whatever follows the path doesn't get caught on wall corners.
+/// `walkable` has to be false outside of the grid, or the search never ends on unreachable goals.
+#[derive(Clone, Debug)]
+pub struct Search {
+    start: IVec2,
+    goal: IVec2,
+    open: BinaryHeap<OpenCell>,
+    came_from: HashMap<IVec2, IVec2>,
+    costs: HashMap<IVec2, i32>,
+}
+
+impl Search {
+    pub fn new(start: IVec2, goal: IVec2) -> Self {
+        let mut search = Self {
+            start,
+            goal,
+            open: BinaryHeap::new(),
+            came_from: HashMap::new(),
+            costs: HashMap::new(),
+        };
+        search.restart();
+        search
+    }
+
+    /// Forgets the progress, for when the grid changed under the search
+    pub fn restart(&mut self) {
+        self.open.clear();
+        self.came_from.clear();
+        self.costs.clear();
+
+        self.open.push(OpenCell {
+            estimate: heuristic(self.start, self.goal),
+            cost: 0,
+            cell: self.start,
+        });
+        self.costs.insert(self.start, 0);
+    }
+
+    /// Expands at most `budget` cells, `budget` is left with what wasn't used
+    pub fn step(&mut self, budget: &mut usize, walkable: impl Fn(IVec2) -> bool) -> SearchState {
+        if !walkable(self.goal) {
+            return SearchState::NotFound;
+        }
+
+        while *budget > 0 {
+            let OpenCell { cost, cell, .. } = match self.open.pop() {
+                Some(open_cell) => open_cell,
+                None => return SearchState::NotFound,
+            };
+
+            if cell == self.goal {
+                return SearchState::Found(self.path_to(cell));
+            }
+
+            // a cheaper way to this cell was found after it was queued
+            if self.costs.get(&cell).map_or(false, |&best| best < cost) {
+                continue;
+            }
+
+            *budget -= 1;
+
+            for (x, y) in DIRECTIONS {
+                let direction = IVec2::new(x, y);
+                let next = cell + direction;
+                if !walkable(next) {
+                    continue;
+                }
+
+                let diagonal = direction.x != 0 && direction.y != 0;
+                if diagonal
+                    && !(walkable(cell + IVec2::new(direction.x, 0))
+                        && walkable(cell + IVec2::new(0, direction.y)))
+                {
+                    continue;
+                }
+
+                let next_cost = cost
+                    + if diagonal {
+                        DIAGONAL_COST
+                    } else {
+                        STRAIGHT_COST
+                    };
+
+                if self.costs.get(&next).map_or(true, |&best| next_cost < best) {
+                    self.costs.insert(next, next_cost);
+                    self.came_from.insert(next, cell);
+                    self.open.push(OpenCell {
+                        estimate: next_cost + heuristic(next, self.goal),
+                        cost: next_cost,
+                        cell: next,
+                    });
+                }
+            }
+        }
+
+        SearchState::Searching
+    }
+
+    fn path_to(&self, cell: IVec2) -> Vec<IVec2> {
+        let mut path = vec![cell];
+        let mut current = cell;
+        while let Some(&previous) = self.came_from.get(&current) {
+            path.push(previous);
+            current = previous;
+        }
+        path.reverse();
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// Walkable cells of a map drawn row by row from `y = 0`, `#` is blocked
+    fn grid(rows: &[&str]) -> impl Fn(IVec2) -> bool {
+        let walkable: HashSet<IVec2> = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.chars()
+                    .enumerate()
+                    .filter(|&(_, c)| c != '#')
+                    .map(move |(x, _)| IVec2::new(x as i32, y as i32))
+            })
+            .collect();
+
+        move |cell| walkable.contains(&cell)
+    }
+
+    fn search(from: (i32, i32), to: (i32, i32), walkable: impl Fn(IVec2) -> bool) -> SearchState {
+        Search::new(from.into(), to.into()).step(&mut usize::MAX, walkable)
+    }
+
+    fn cells(cells: &[(i32, i32)]) -> Vec<IVec2> {
+        cells.iter().map(|&cell| cell.into()).collect()
+    }
+
+    /// Panics unless every step goes to a walkable neighbor without cutting a corner
+    fn path_cost(path: &[IVec2], walkable: impl Fn(IVec2) -> bool) -> i32 {
+        path.windows(2)
+            .map(|step| {
+                let (from, to) = (step[0], step[1]);
+                let direction = to - from;
+                assert!(walkable(to), "{:?} is blocked", to);
+                assert!(
+                    direction != IVec2::ZERO && direction.x.abs() <= 1 && direction.y.abs() <= 1,
+                    "{:?} is not a step to a neighbor",
+                    step
+                );
+
+                if direction.x != 0 && direction.y != 0 {
+                    assert!(
+                        walkable(from + IVec2::new(direction.x, 0))
+                            && walkable(from + IVec2::new(0, direction.y)),
+                        "{:?} cuts a corner",
+                        step
+                    );
+                    DIAGONAL_COST
+                } else {
+                    STRAIGHT_COST
+                }
+            })
+            .sum()
+    }
+
+    #[test]
+    fn straight_path() {
+        assert_eq!(
+            search((0, 0), (4, 0), grid(&["....."])),
+            SearchState::Found(cells(&[(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)]))
+        );
+    }
+
+    #[test]
+    fn start_is_the_goal() {
+        assert_eq!(
+            search((1, 1), (1, 1), grid(&["...", "...", "..."])),
+            SearchState::Found(cells(&[(1, 1)]))
+        );
+    }
+
+    #[test]
+    fn detour_around_a_wall() {
+        let walkable = grid(&[".....", "..#..", "....."]);
+
+        let path = match search((0, 1), (4, 1), &walkable) {
+            SearchState::Found(path) => path,
+            state => panic!("{:?}", state),
+        };
+
+        assert_eq!(path.first(), Some(&IVec2::new(0, 1)));
+        assert_eq!(path.last(), Some(&IVec2::new(4, 1)));
+        // two diagonal steps around the wall
+        assert_eq!(
+            path_cost(&path, &walkable),
+            2 * DIAGONAL_COST + 2 * STRAIGHT_COST
+        );
+    }
+
+    #[test]
+    fn no_diagonal_past_a_blocked_cell() {
+        assert_eq!(
+            search((0, 0), (1, 1), grid(&["..", "#."])),
+            SearchState::Found(cells(&[(0, 0), (1, 0), (1, 1)]))
+        );
+
+        // the only way would squeeze between two blocked cells
+        assert_eq!(
+            search((0, 0), (1, 1), grid(&[".#", "#."])),
+            SearchState::NotFound
+        );
+    }
+
+    #[test]
+    fn unreachable_goal_is_not_found() {
+        let walkable = grid(&["..#.", "..#.", "..#."]);
+
+        assert_eq!(search((0, 0), (3, 1), &walkable), SearchState::NotFound);
+        // in a wall
+        assert_eq!(search((0, 0), (2, 1), &walkable), SearchState::NotFound);
+        // outside of the grid
+        assert_eq!(search((0, 0), (9, 9), &walkable), SearchState::NotFound);
+    }
+
+    #[test]
+    fn resumes_when_out_of_budget() {
+        let walkable = grid(&["...................."]);
+        let mut search = Search::new(IVec2::new(0, 0), IVec2::new(19, 0));
+
+        let mut steps = 0;
+        let path = loop {
+            let mut budget = 3;
+            match search.step(&mut budget, &walkable) {
+                SearchState::Searching => assert_eq!(budget, 0),
+                SearchState::Found(path) => break path,
+                SearchState::NotFound => panic!("no path"),
+            }
+            steps += 1;
+        };
+
+        assert!(steps > 1, "found in a single step");
+        assert_eq!(path, (0..20).map(|x| IVec2::new(x, 0)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn unused_budget_is_left() {
+        let mut budget = 100;
+        let state =
+            Search::new(IVec2::new(0, 0), IVec2::new(2, 0)).step(&mut budget, grid(&["..."]));
+
+        assert!(matches!(state, SearchState::Found(_)));
+        assert_eq!(budget, 98);
+    }
+
+    #[test]
+    fn restart_searches_the_new_grid() {
+        let before = grid(&[".....", ".....", "....."]);
+        let after = grid(&["..#..", "..#..", "....."]);
+
+        let mut search = Search::new(IVec2::new(0, 0), IVec2::new(4, 0));
+        assert_eq!(search.step(&mut 2, &before), SearchState::Searching);
+
+        search.restart();
+        let path = match search.step(&mut usize::MAX, &after) {
+            SearchState::Found(path) => path,
+            state => panic!("{:?}", state),
+        };
+
+        assert!(path.contains(&IVec2::new(2, 2)), "{:?}", path);
+        path_cost(&path, &after);
+    }
+}
@@ -0,0 +1,344 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use bevy::{input::InputSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+/// Turns raw input into named actions, gameplay only ever reads `Actions`
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Bindings::load())
+            .init_resource::<Actions>()
+            .init_resource::<Rebinding>()
+            .add_event::<RebindAction>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                Self::update_actions.after(InputSystem),
+            )
+            .add_system(Self::start_rebinding)
+            .add_system(Self::save_bindings);
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Display,
+    EnumIter,
+    Serialize,
+    Deserialize,
+)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Sprint,
+    Interact,
+    /// Activates the focused menu button
+    Confirm,
+    Pause,
+    ToggleFullscreen,
+    ToggleWorldMap,
+    /// Walks the player to the spot under the cursor
+    MoveTo,
+}
+
+impl Action {
+    fn default_binding(self) -> ActionBinding {
+        use GamepadButtonType::*;
+        use KeyCode::*;
+
+        let (keys, buttons) = match self {
+            Action::MoveUp => (vec![W, Up], vec![DPadUp]),
+            Action::MoveDown => (vec![S, Down], vec![DPadDown]),
+            Action::MoveLeft => (vec![A, Left], vec![DPadLeft]),
+            Action::MoveRight => (vec![D, Right], vec![DPadRight]),
+            Action::Sprint => (vec![LShift], vec![LeftThumb]),
+            Action::Interact => (vec![E], vec![West]),
+            Action::Confirm => (vec![Return, Space], vec![South]),
+            Action::Pause => (vec![Escape], vec![Start]),
+            Action::ToggleFullscreen => (vec![F11], vec![]),
+            Action::ToggleWorldMap => (vec![M], vec![Select]),
+            Action::MoveTo => (vec![], vec![RightTrigger]),
+        };
+
+        let mouse_buttons = match self {
+            Action::MoveTo => vec![MouseButton::Left],
+            _ => vec![],
+        };
+
+        ActionBinding {
+            keys,
+            buttons,
+            mouse_buttons,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionBinding {
+    pub keys: Vec<KeyCode>,
+    pub buttons: Vec<GamepadButtonType>,
+    pub mouse_buttons: Vec<MouseButton>,
+}
+
+/// Keys, gamepad and mouse buttons bound to every action
+///
+/// Saved to `settings/bindings.ron` whenever they change.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bindings {
+    pub actions: BTreeMap<Action, ActionBinding>,
+    /// Stick deflection below which the stick counts as centered, from 0 to 1
+    pub stick_deadzone: f32,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            actions: Action::iter()
+                .map(|action| (action, action.default_binding()))
+                .collect(),
+            stick_deadzone: 0.2,
+        }
+    }
+}
+
+impl Bindings {
+    pub const PATH: &'static str = "settings/bindings.ron";
+
+    /// Reads the saved bindings, actions missing from the file keep their default keys
+    pub fn load() -> Self {
+        let mut bindings: Self = match fs::read_to_string(Self::PATH) {
+            Ok(text) => ron::from_str(&text).unwrap_or_else(|error| {
+                error!("Can't read `{}`: {}", Self::PATH, error);
+                Self::default()
+            }),
+            Err(_) => return Self::default(),
+        };
+
+        for action in Action::iter() {
+            bindings
+                .actions
+                .entry(action)
+                .or_insert_with(|| action.default_binding());
+        }
+
+        bindings
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(directory) = Path::new(Self::PATH).parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(Self::PATH, text)?;
+        Ok(())
+    }
+
+    pub fn binding(&self, action: Action) -> Option<&ActionBinding> {
+        self.actions.get(&action)
+    }
+
+    pub fn bind_keys(&mut self, action: Action, keys: Vec<KeyCode>) {
+        self.actions.entry(action).or_default().keys = keys;
+    }
+
+    pub fn bind_buttons(&mut self, action: Action, buttons: Vec<GamepadButtonType>) {
+        self.actions.entry(action).or_default().buttons = buttons;
+    }
+
+    pub fn bind_mouse_buttons(&mut self, action: Action, mouse_buttons: Vec<MouseButton>) {
+        self.actions.entry(action).or_default().mouse_buttons = mouse_buttons;
+    }
+
+    /// Radial deadzone, the rest of the range is stretched back to 0..1
+    fn apply_deadzone(&self, stick: Vec2) -> Vec2 {
+        let length = stick.length();
+        if length <= self.stick_deadzone {
+            return Vec2::ZERO;
+        }
+
+        let scaled = ((length - self.stick_deadzone) / (1. - self.stick_deadzone)).min(1.);
+        stick / length * scaled
+    }
+}
+
+/// Stick deflection that holds the matching move action, for menus
+const STICK_PRESS_THRESHOLD: f32 = 0.5;
+
+/// Actions held, pressed and released this frame
+#[derive(Clone, Debug, Default)]
+pub struct Actions {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    just_released: HashSet<Action>,
+    /// Left stick of the gamepads, past the deadzone
+    stick: Vec2,
+}
+
+impl Actions {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    /// -1, 0 or 1, depending on which of the two actions is held
+    pub fn axis(&self, negative: Action, positive: Action) -> f32 {
+        (self.pressed(positive) as i32 - self.pressed(negative) as i32) as f32
+    }
+
+    /// Analog stick movement, or the move actions when the stick is centered
+    pub fn movement(&self) -> Vec2 {
+        if self.stick != Vec2::ZERO {
+            return self.stick;
+        }
+
+        Vec2::new(
+            self.axis(Action::MoveLeft, Action::MoveRight),
+            self.axis(Action::MoveDown, Action::MoveUp),
+        )
+    }
+}
+
+/// Binds the next pressed key, gamepad or mouse button to the action, replacing its keys or buttons
+#[derive(Clone, Debug)]
+pub struct RebindAction(pub Action);
+
+/// Action waiting for a key or a button, no actions fire meanwhile
+#[derive(Default)]
+pub struct Rebinding(pub Option<Action>);
+
+impl ActionsPlugin {
+    fn update_actions(
+        keyboard: Res<Input<KeyCode>>,
+        mouse_buttons: Res<Input<MouseButton>>,
+        gamepads: Res<Gamepads>,
+        gamepad_buttons: Res<Input<GamepadButton>>,
+        gamepad_axes: Res<Axis<GamepadAxis>>,
+        mut bindings: ResMut<Bindings>,
+        mut rebinding: ResMut<Rebinding>,
+        mut actions: ResMut<Actions>,
+    ) {
+        if let Some(action) = rebinding.0 {
+            *actions = Actions::default();
+
+            if let Some(&key) = keyboard.get_just_pressed().next() {
+                bindings.bind_keys(action, vec![key]);
+                rebinding.0 = None;
+                info!("{} is bound to {:?}", action, key);
+            } else if let Some(&GamepadButton(_, button)) =
+                gamepad_buttons.get_just_pressed().next()
+            {
+                bindings.bind_buttons(action, vec![button]);
+                rebinding.0 = None;
+                info!("{} is bound to {:?}", action, button);
+            } else if let Some(&button) = mouse_buttons.get_just_pressed().next() {
+                bindings.bind_mouse_buttons(action, vec![button]);
+                rebinding.0 = None;
+                info!("{} is bound to {:?}", action, button);
+            }
+
+            return;
+        }
+
+        let stick = gamepads
+            .iter()
+            .map(|&gamepad| {
+                let axis = |axis_type| {
+                    gamepad_axes
+                        .get(GamepadAxis(gamepad, axis_type))
+                        .unwrap_or(0.)
+                };
+                let stick = Vec2::new(
+                    axis(GamepadAxisType::LeftStickX),
+                    axis(GamepadAxisType::LeftStickY),
+                );
+                bindings.apply_deadzone(stick)
+            })
+            .find(|stick| *stick != Vec2::ZERO)
+            .unwrap_or(Vec2::ZERO);
+
+        let stick_pressed = |action| match action {
+            Action::MoveUp => stick.y > STICK_PRESS_THRESHOLD,
+            Action::MoveDown => stick.y < -STICK_PRESS_THRESHOLD,
+            Action::MoveLeft => stick.x < -STICK_PRESS_THRESHOLD,
+            Action::MoveRight => stick.x > STICK_PRESS_THRESHOLD,
+            _ => false,
+        };
+
+        let mut state = Actions { stick, ..default() };
+
+        for action in Action::iter() {
+            let binding = match bindings.binding(action) {
+                Some(binding) => binding,
+                None => continue,
+            };
+
+            let pressed = stick_pressed(action)
+                || binding.keys.iter().any(|&key| keyboard.pressed(key))
+                || binding
+                    .mouse_buttons
+                    .iter()
+                    .any(|&button| mouse_buttons.pressed(button))
+                || gamepads.iter().any(|&gamepad| {
+                    binding
+                        .buttons
+                        .iter()
+                        .any(|&button| gamepad_buttons.pressed(GamepadButton(gamepad, button)))
+                });
+
+            // comparing with the last frame also catches the stick crossing the threshold
+            let was_pressed = actions.pressed(action);
+            if pressed {
+                state.pressed.insert(action);
+                if !was_pressed {
+                    state.just_pressed.insert(action);
+                }
+            } else if was_pressed {
+                state.just_released.insert(action);
+            }
+        }
+
+        *actions = state;
+    }
+
+    fn start_rebinding(
+        mut rebind_events: EventReader<RebindAction>,
+        mut rebinding: ResMut<Rebinding>,
+    ) {
+        if let Some(RebindAction(action)) = rebind_events.iter().last() {
+            rebinding.0 = Some(*action);
+        }
+    }
+
+    fn save_bindings(bindings: Res<Bindings>) {
+        if bindings.is_changed() && !bindings.is_added() {
+            if let Err(error) = bindings.save() {
+                error!("Can't save `{}`: {}", Bindings::PATH, error);
+            }
+        }
+    }
+}
@@ -0,0 +1,429 @@
+use bevy::{prelude::*, render::camera::ScalingMode, window::WindowResized};
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    components::Player,
+    plugins::CameraEffects,
+    systems::{MainCamera, MovementController},
+    AppState,
+};
+
+/// Follows the player with the `MainCamera`, keeping the view inside the current level
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraSettings>()
+            .add_startup_system(Self::spawn_letterbox)
+            .add_system(Self::update_letterbox)
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame).with_system(Self::follow_player),
+            );
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CameraMode {
+    /// Covers the level along its shorter side, at any scale
+    FitLevel,
+    /// Shows `resolution` world pixels, upscaled by the largest whole factor that fits the window
+    PixelPerfect { resolution: UVec2 },
+}
+
+impl CameraMode {
+    /// Reads the mode from `CAMERA_MODE`, which is either `fit_level` or `pixel_perfect`
+    pub fn from_env() -> Self {
+        match std::env::var("CAMERA_MODE").as_deref() {
+            // a 16:9 screen of 30 by 16.875 tiles
+            Ok("pixel_perfect") => CameraMode::PixelPerfect {
+                resolution: UVec2::new(480, 270),
+            },
+            _ => CameraMode::FitLevel,
+        }
+    }
+}
+
+pub struct CameraSettings {
+    pub mode: CameraMode,
+    /// Narrowest view shown in `FitLevel` mode, taller windows get letterbox bars
+    pub min_aspect_ratio: f32,
+    /// Widest view shown in `FitLevel` mode, wider windows get pillarbox bars
+    pub max_aspect_ratio: f32,
+    /// Half size of the box around the focus the player moves in without moving the camera
+    pub deadzone: Vec2,
+    /// How fast the camera catches up with the player, higher is snappier
+    pub smoothing: f32,
+    /// How far ahead of the player the camera looks while it moves
+    pub look_ahead: f32,
+    pub look_ahead_smoothing: f32,
+    /// How fast the camera pans over to a newly selected level
+    pub level_pan_smoothing: f32,
+    /// Turns off screen shake and zoom, and makes scripted pans cut
+    pub reduced_motion: bool,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            mode: CameraMode::from_env(),
+            min_aspect_ratio: 4. / 3.,
+            max_aspect_ratio: 21. / 9.,
+            deadzone: Vec2::new(16., 12.),
+            smoothing: 8.,
+            look_ahead: 24.,
+            look_ahead_smoothing: 3.,
+            level_pan_smoothing: 4.,
+            reduced_motion: std::env::var("REDUCED_MOTION").map_or(false, |value| value == "1"),
+        }
+    }
+}
+
+impl CameraSettings {
+    /// Logical size of the part of the window the view is drawn in, the rest is covered by bars
+    pub fn frame_size(&self, window: &Window) -> Option<Vec2> {
+        let window_size = Vec2::new(window.width(), window.height());
+        if window_size.x <= 0. || window_size.y <= 0. {
+            return None;
+        }
+
+        let frame_size = match self.mode {
+            CameraMode::FitLevel => {
+                let window_aspect_ratio = window_size.x / window_size.y;
+                let aspect_ratio =
+                    window_aspect_ratio.clamp(self.min_aspect_ratio, self.max_aspect_ratio);
+
+                if window_aspect_ratio > aspect_ratio {
+                    Vec2::new(window_size.y * aspect_ratio, window_size.y)
+                } else {
+                    Vec2::new(window_size.x, window_size.x / aspect_ratio)
+                }
+            }
+            CameraMode::PixelPerfect { resolution } => {
+                // whole factors of physical pixels, the logical size may be fractional
+                let resolution = resolution.as_vec2();
+                let physical_size = Vec2::new(
+                    window.physical_width() as f32,
+                    window.physical_height() as f32,
+                );
+                let scale = (physical_size / resolution).min_element().floor().max(1.);
+                resolution * scale / window.scale_factor() as f32
+            }
+        };
+
+        Some(frame_size)
+    }
+}
+
+/// State of the follow logic, lives on the `MainCamera`
+#[derive(Clone, Debug, Default, Component)]
+pub struct CameraFollow {
+    /// Point the deadzone is centered on
+    focus: Vec2,
+    look_ahead: Vec2,
+    /// Center and size of the view, before they are written to the camera
+    center: Vec2,
+    view: Vec2,
+    level_iid: String,
+    /// Set while the camera pans over to another level
+    panning: bool,
+    /// The first frame jumps straight to the player
+    placed: bool,
+}
+
+#[derive(Copy, Clone, Debug, Component)]
+enum LetterboxBar {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// Size of the view that covers the level along its shorter side
+fn fit_view(level_size: Vec2, aspect_ratio: f32) -> Vec2 {
+    if level_size.x / level_size.y > aspect_ratio {
+        // level is wider than the screen
+        Vec2::new(level_size.y * aspect_ratio, level_size.y)
+    } else {
+        // level is taller than the screen
+        Vec2::new(level_size.x, level_size.x / aspect_ratio)
+    }
+}
+
+/// Keeps the view inside the level, a view bigger than the level sticks to its bottom left corner
+fn clamp_to_level(center: Vec2, view: Vec2, level_min: Vec2, level_size: Vec2) -> Vec2 {
+    let min = level_min + view / 2.;
+    let max = level_min + level_size - view / 2.;
+
+    Vec2::new(
+        if min.x < max.x {
+            center.x.clamp(min.x, max.x)
+        } else {
+            min.x
+        },
+        if min.y < max.y {
+            center.y.clamp(min.y, max.y)
+        } else {
+            min.y
+        },
+    )
+}
+
+impl CameraPlugin {
+    fn spawn_letterbox(mut commands: Commands) {
+        use LetterboxBar::*;
+
+        for bar in [Left, Right, Top, Bottom] {
+            commands
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    },
+                    color: Color::BLACK.into(),
+                    ..default()
+                })
+                .insert(Name::new("LetterboxBar"))
+                .insert(bar)
+                // there is nothing to click on the bars
+                .insert(Interaction::default());
+        }
+    }
+
+    /// Sizes the bars around the view whenever the window is resized
+    fn update_letterbox(
+        windows: Res<Windows>,
+        settings: Res<CameraSettings>,
+        mut resize_events: EventReader<WindowResized>,
+        mut bar_query: Query<(&LetterboxBar, &mut Style)>,
+        added_query: Query<(), Added<LetterboxBar>>,
+    ) {
+        let resized = resize_events.iter().count() > 0;
+        if !resized && !settings.is_changed() && added_query.is_empty() {
+            return;
+        }
+
+        let window = match windows.get_primary() {
+            Some(window) => window,
+            None => return,
+        };
+
+        let frame_size = match settings.frame_size(window) {
+            Some(frame_size) => frame_size,
+            None => return,
+        };
+
+        let window_size = Vec2::new(window.width(), window.height());
+        let bar_size = ((window_size - frame_size) / 2.).max(Vec2::ZERO);
+
+        for (bar, mut style) in bar_query.iter_mut() {
+            let edge = Val::Px(0.);
+            let full = Val::Percent(100.);
+
+            // left and right bars span the whole height, top and bottom ones fill the gap between them
+            let (size, position) = match bar {
+                LetterboxBar::Left => (
+                    Size::new(Val::Px(bar_size.x), full),
+                    Rect {
+                        left: edge,
+                        top: edge,
+                        ..default()
+                    },
+                ),
+                LetterboxBar::Right => (
+                    Size::new(Val::Px(bar_size.x), full),
+                    Rect {
+                        right: edge,
+                        top: edge,
+                        ..default()
+                    },
+                ),
+                LetterboxBar::Top => (
+                    Size::new(
+                        Val::Px(frame_size.x.min(window_size.x)),
+                        Val::Px(bar_size.y),
+                    ),
+                    Rect {
+                        left: Val::Px(bar_size.x),
+                        top: edge,
+                        ..default()
+                    },
+                ),
+                LetterboxBar::Bottom => (
+                    Size::new(
+                        Val::Px(frame_size.x.min(window_size.x)),
+                        Val::Px(bar_size.y),
+                    ),
+                    Rect {
+                        left: Val::Px(bar_size.x),
+                        bottom: edge,
+                        ..default()
+                    },
+                ),
+            };
+
+            style.size = size;
+            style.position = position;
+        }
+    }
+
+    fn follow_player(
+        time: Res<Time>,
+        windows: Res<Windows>,
+        settings: Res<CameraSettings>,
+        level_selection: Res<LevelSelection>,
+        ldtk_levels: Res<Assets<LdtkLevel>>,
+        mut camera_query: Query<
+            (
+                &mut OrthographicProjection,
+                &mut Transform,
+                &mut CameraFollow,
+                Option<&mut CameraEffects>,
+            ),
+            (With<MainCamera>, Without<Player>),
+        >,
+        player_query: Query<(&Transform, &MovementController), With<Player>>,
+        level_query: Query<
+            (&Transform, &Handle<LdtkLevel>),
+            (Without<OrthographicProjection>, Without<Player>),
+        >,
+    ) {
+        let (player_transform, movement) = match player_query.get_single() {
+            Ok(player) => player,
+            Err(_) => return,
+        };
+
+        let (mut projection, mut camera_transform, mut follow, effects) =
+            match camera_query.get_single_mut() {
+                Ok(camera) => camera,
+                Err(_) => return,
+            };
+
+        let level = level_query.iter().find_map(|(transform, handle)| {
+            ldtk_levels
+                .get(handle)
+                .filter(|ldtk_level| level_selection.is_match(&0, &ldtk_level.level))
+                .map(|ldtk_level| (transform, &ldtk_level.level))
+        });
+
+        let (level_transform, level) = match level {
+            Some(level) => level,
+            None => return,
+        };
+
+        let window = match windows.get_primary() {
+            Some(window) => window,
+            None => return,
+        };
+
+        let frame_size = match settings.frame_size(window) {
+            Some(frame_size) => frame_size,
+            None => return,
+        };
+
+        let level_min = level_transform.translation.truncate();
+        let level_size = IVec2::new(level.px_wid, level.px_hei).as_vec2();
+        let view = match settings.mode {
+            CameraMode::FitLevel => fit_view(level_size, frame_size.x / frame_size.y),
+            CameraMode::PixelPerfect { resolution } => resolution.as_vec2(),
+        };
+
+        let player_position = player_transform.translation.truncate();
+        let delta = time.delta_seconds();
+        let smooth = |rate: f32| 1. - (-rate * delta).exp();
+
+        if !follow.placed {
+            follow.focus = player_position;
+        }
+
+        // the focus only moves once the player pushes against the deadzone
+        let offset = player_position - follow.focus;
+        follow.focus += offset - offset.clamp(-settings.deadzone, settings.deadzone);
+
+        let direction = movement.0.normalize_or_zero();
+        follow.look_ahead = follow.look_ahead.lerp(
+            direction * settings.look_ahead,
+            smooth(settings.look_ahead_smoothing),
+        );
+
+        if follow.level_iid != level.iid {
+            follow.level_iid = level.iid.clone();
+            follow.panning = follow.placed;
+        }
+
+        let target = clamp_to_level(
+            follow.focus + follow.look_ahead,
+            view,
+            level_min,
+            level_size,
+        );
+
+        if follow.placed {
+            let rate = if follow.panning {
+                settings.level_pan_smoothing
+            } else {
+                settings.smoothing
+            };
+
+            follow.center = follow.center.lerp(target, smooth(rate));
+            follow.view = match settings.mode {
+                CameraMode::FitLevel => follow.view.lerp(view, smooth(rate)),
+                // a fractional view size would break the whole-number scaling
+                CameraMode::PixelPerfect { .. } => view,
+            };
+
+            if follow.panning && follow.center.distance(target) < 0.5 {
+                follow.panning = false;
+            }
+        } else {
+            follow.center = target;
+            follow.view = view;
+            follow.placed = true;
+        }
+
+        // while panning the view is still partly over the previous level
+        if !follow.panning {
+            follow.center = clamp_to_level(follow.center, follow.view, level_min, level_size);
+        }
+
+        let mut zoom = 1.;
+        let mut shake = Vec2::ZERO;
+        if let Some(mut effects) = effects {
+            // the follow logic picks up from wherever the pan left the camera
+            if let Some(pan_center) = effects.pan_center(follow.center) {
+                follow.center = clamp_to_level(pan_center, follow.view, level_min, level_size);
+            }
+
+            if effects.take_cut() {
+                follow.placed = false;
+            }
+
+            zoom = effects.zoom_factor();
+            shake = effects.shake_offset();
+        }
+
+        if let CameraMode::PixelPerfect { resolution } = settings.mode {
+            // zooms step through whole upscaling factors, so world pixels stay whole screen pixels
+            let scale = (frame_size.x * window.scale_factor() as f32 / resolution.x as f32).round();
+            zoom = scale / (scale / zoom).round().max(1.);
+        }
+
+        // the parts of the window outside the frame are covered by the letterbox
+        let window_size = Vec2::new(window.width(), window.height());
+        let projected = follow.view * zoom * window_size / frame_size;
+
+        let mut center = follow.center + shake;
+        if let CameraMode::PixelPerfect { .. } = settings.mode {
+            // the edges of the view fall on whole world pixels
+            center = (center - projected / 2.).round() + projected / 2.;
+        }
+
+        projection.scaling_mode = ScalingMode::None;
+        projection.left = -projected.x / 2.;
+        projection.right = projected.x / 2.;
+        projection.bottom = -projected.y / 2.;
+        projection.top = projected.y / 2.;
+
+        camera_transform.translation.x = center.x;
+        camera_transform.translation.y = center.y;
+    }
+}
@@ -0,0 +1,274 @@
+use bevy::prelude::*;
+
+use crate::{plugins::CameraSettings, systems::MainCamera};
+
+/// Shake, zoom and scripted pans of the `MainCamera`, triggered by events
+///
+/// The effects are layered over the follow logic of `CameraPlugin`,
+/// which takes over again once they end.
+pub struct CameraEffectsPlugin;
+
+impl Plugin for CameraEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CameraShake>()
+            .add_event::<CameraZoom>()
+            .add_event::<CameraPan>()
+            .add_system(Self::start_effects)
+            .add_system(Self::tick_effects);
+    }
+}
+
+/// Adds trauma to the camera, the shake grows with the square of the trauma
+#[derive(Clone, Debug)]
+pub struct CameraShake {
+    /// From 0 to 1, the total trauma is capped at 1
+    pub trauma: f32,
+}
+
+/// Temporarily scales the view, `factor` below 1 zooms in
+#[derive(Clone, Debug)]
+pub struct CameraZoom {
+    pub factor: f32,
+    /// Seconds, including easing in and out
+    pub duration: f32,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum PanTarget {
+    Position(Vec2),
+    Entity(Entity),
+}
+
+/// Moves the camera to a target, holds it there, then lets the camera follow the player again
+#[derive(Clone, Debug)]
+pub struct CameraPan {
+    pub target: PanTarget,
+    /// Seconds the camera takes to get to the target
+    pub duration: f32,
+    /// Seconds the camera stays on the target
+    pub hold: f32,
+    pub easing: Easing,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicInOut,
+}
+
+impl Easing {
+    /// Maps progress from 0 to 1 onto the curve
+    pub fn ease(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => t * (2. - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2. * t * t
+                } else {
+                    1. - (-2. * t + 2.).powi(2) / 2.
+                }
+            }
+            Easing::CubicInOut => {
+                if t < 0.5 {
+                    4. * t * t * t
+                } else {
+                    1. - (-2. * t + 2.).powi(3) / 2.
+                }
+            }
+        }
+    }
+}
+
+/// Trauma lost per second
+const TRAUMA_DECAY: f32 = 1.2;
+/// Offset of the camera at full trauma, in pixels
+const MAX_SHAKE_OFFSET: f32 = 6.;
+/// Seconds a zoom takes to reach its factor and to go back
+const ZOOM_EASING_TIME: f32 = 0.25;
+
+#[derive(Clone, Debug)]
+struct ActiveZoom {
+    factor: f32,
+    duration: f32,
+    elapsed: f32,
+}
+
+#[derive(Clone, Debug)]
+struct ActivePan {
+    target: PanTarget,
+    /// World position of the target, updated while the pan runs
+    position: Option<Vec2>,
+    /// Where the camera was when the pan started
+    from: Option<Vec2>,
+    duration: f32,
+    hold: f32,
+    easing: Easing,
+    elapsed: f32,
+}
+
+/// Effects running on the `MainCamera`
+#[derive(Clone, Debug, Default, Component)]
+pub struct CameraEffects {
+    trauma: f32,
+    shake_time: f32,
+    zoom: Option<ActiveZoom>,
+    pan: Option<ActivePan>,
+    /// The camera has to jump back to the player instead of panning
+    cut: bool,
+}
+
+impl CameraEffects {
+    /// Center of the view while a pan runs, `center` is where the camera would be otherwise
+    pub fn pan_center(&mut self, center: Vec2) -> Option<Vec2> {
+        let pan = self.pan.as_mut()?;
+        let position = pan.position?;
+        let from = *pan.from.get_or_insert(center);
+
+        let progress = if pan.duration > 0. {
+            pan.elapsed / pan.duration
+        } else {
+            1.
+        };
+
+        Some(from.lerp(position, pan.easing.ease(progress)))
+    }
+
+    pub fn zoom_factor(&self) -> f32 {
+        match &self.zoom {
+            Some(zoom) => {
+                let easing_time = ZOOM_EASING_TIME.min(zoom.duration / 2.);
+                let blend = if easing_time > 0. {
+                    (zoom.elapsed.min(zoom.duration - zoom.elapsed) / easing_time).clamp(0., 1.)
+                } else {
+                    1.
+                };
+
+                1. + (zoom.factor - 1.) * Easing::QuadInOut.ease(blend)
+            }
+            None => 1.,
+        }
+    }
+
+    pub fn shake_offset(&self) -> Vec2 {
+        let shake = self.trauma * self.trauma;
+        if shake <= 0. {
+            return Vec2::ZERO;
+        }
+
+        // a few incommensurable sines are irregular enough to pass for noise
+        let t = self.shake_time;
+        let noise = Vec2::new(
+            (t * 37.).sin() * 0.6 + (t * 71.).sin() * 0.4,
+            (t * 43.).sin() * 0.6 + (t * 89.).sin() * 0.4,
+        );
+
+        noise * shake * MAX_SHAKE_OFFSET
+    }
+
+    /// Whether the camera has to jump back to the player, resets the request
+    pub fn take_cut(&mut self) -> bool {
+        std::mem::take(&mut self.cut)
+    }
+}
+
+impl CameraEffectsPlugin {
+    fn start_effects(
+        settings: Res<CameraSettings>,
+        mut shake_events: EventReader<CameraShake>,
+        mut zoom_events: EventReader<CameraZoom>,
+        mut pan_events: EventReader<CameraPan>,
+        mut camera_query: Query<&mut CameraEffects, With<MainCamera>>,
+    ) {
+        let mut effects = match camera_query.get_single_mut() {
+            Ok(effects) => effects,
+            Err(_) => return,
+        };
+
+        // with reduced motion there is no shake or zoom, and pans become cuts
+        for event in shake_events.iter() {
+            if !settings.reduced_motion {
+                effects.trauma = (effects.trauma + event.trauma).clamp(0., 1.);
+            }
+        }
+
+        for event in zoom_events.iter() {
+            if !settings.reduced_motion {
+                effects.zoom = Some(ActiveZoom {
+                    factor: event.factor,
+                    duration: event.duration,
+                    elapsed: 0.,
+                });
+            }
+        }
+
+        for event in pan_events.iter() {
+            let duration = if settings.reduced_motion {
+                0.
+            } else {
+                event.duration
+            };
+
+            effects.pan = Some(ActivePan {
+                target: event.target,
+                position: None,
+                from: None,
+                duration,
+                hold: event.hold,
+                easing: event.easing,
+                elapsed: 0.,
+            });
+        }
+    }
+
+    fn tick_effects(
+        time: Res<Time>,
+        settings: Res<CameraSettings>,
+        mut camera_query: Query<&mut CameraEffects, With<MainCamera>>,
+        target_query: Query<&GlobalTransform>,
+    ) {
+        let delta = time.delta_seconds();
+
+        for mut effects in camera_query.iter_mut() {
+            effects.trauma = (effects.trauma - TRAUMA_DECAY * delta).max(0.);
+            effects.shake_time = if effects.trauma > 0. {
+                effects.shake_time + delta
+            } else {
+                0.
+            };
+
+            if let Some(zoom) = &mut effects.zoom {
+                zoom.elapsed += delta;
+                if zoom.elapsed >= zoom.duration {
+                    effects.zoom = None;
+                }
+            }
+
+            if let Some(pan) = &mut effects.pan {
+                pan.position = match pan.target {
+                    PanTarget::Position(position) => Some(position),
+                    PanTarget::Entity(entity) => match target_query.get(entity) {
+                        Ok(transform) => Some(transform.translation.truncate()),
+                        // a despawned target ends the pan
+                        Err(_) => None,
+                    },
+                };
+
+                // the pan only starts moving once the follow logic has seen it
+                if pan.from.is_some() {
+                    pan.elapsed += delta;
+                }
+
+                if pan.position.is_none() || pan.elapsed >= pan.duration + pan.hold {
+                    effects.pan = None;
+                    effects.cut = settings.reduced_motion;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{components::*, plugins::Interacted, AppState};
+
+pub struct ChestPlugin;
+
+/// Sent when the player empties a chest
+#[derive(Clone, Debug)]
+pub struct ChestOpened {
+    pub chest: Entity,
+    pub opener: Entity,
+    pub items: Vec<Item>,
+}
+
+impl Plugin for ChestPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ChestOpened>()
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(Self::open_chests)
+                    .with_system(Self::update_chest_sprites),
+            )
+            .register_ldtk_entity::<ChestBundle>("Chest");
+    }
+}
+
+impl ChestPlugin {
+    fn open_chests(
+        mut interacted_events: EventReader<Interacted>,
+        mut actor_query: Query<&mut Inventory>,
+        mut chest_query: Query<(&mut Chest, &mut ChestContents, &mut Interactable)>,
+        mut chest_events: EventWriter<ChestOpened>,
+    ) {
+        for &Interacted { actor, target } in interacted_events.iter() {
+            let (mut chest, mut contents, mut interactable) = match chest_query.get_mut(target) {
+                Ok(chest) => chest,
+                Err(_) => continue,
+            };
+
+            if *chest != Chest::Closed {
+                continue;
+            }
+
+            let mut inventory = match actor_query.get_mut(actor) {
+                Ok(inventory) => inventory,
+                Err(_) => continue,
+            };
+
+            *chest = Chest::Opened;
+            interactable.enabled = false;
+
+            let items = std::mem::take(&mut contents.0);
+            for &item in items.iter() {
+                inventory.add(item);
+            }
+
+            chest_events.send(ChestOpened {
+                chest: target,
+                opener: actor,
+                items,
+            });
+        }
+    }
+
+    fn update_chest_sprites(
+        mut chest_query: Query<(&Chest, &mut TextureAtlasSprite), Changed<Chest>>,
+    ) {
+        for (chest, mut sprite) in chest_query.iter_mut() {
+            sprite.index = match chest {
+                Chest::Closed => CHEST_CLOSED_SPRITE,
+                Chest::Opened => CHEST_OPENED_SPRITE,
+            };
+        }
+    }
+}
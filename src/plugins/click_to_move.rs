@@ -0,0 +1,187 @@
+use bevy::{prelude::*, render::camera::OrthographicProjection};
+
+use crate::{
+    components::Player,
+    plugins::{Action, Actions, NavGrid, PathQueries, PathQueryId, PathResult, TransitionState},
+    systems::{MainCamera, MovementController},
+    AppState,
+};
+
+/// Walks the player to the clicked spot, around the walls of the loaded levels
+///
+/// Clicks are read through `Action::MoveTo`, and ignored over UI nodes with an `Interaction`.
+/// Any movement input takes the player back.
+pub struct ClickToMovePlugin;
+
+impl Plugin for ClickToMovePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::InGame)
+                .with_system(Self::click_to_move)
+                .with_system(Self::replan_paths)
+                .with_system(Self::follow_path),
+        );
+    }
+}
+
+/// Distance at which a waypoint counts as reached, in pixels
+const WAYPOINT_REACHED_DISTANCE: f32 = 2.;
+
+/// Path the player is walking, replaces the movement input until it ends
+#[derive(Clone, Debug, Component)]
+pub struct PathFollow {
+    goal: Vec2,
+    /// Search for a new path, the old waypoints are walked meanwhile
+    query: Option<PathQueryId>,
+    /// World positions still ahead, the next one first
+    waypoints: Vec<Vec2>,
+}
+
+/// World position under the cursor, for a centered orthographic projection
+fn cursor_world_position(
+    window: &Window,
+    camera_transform: &GlobalTransform,
+    projection: &OrthographicProjection,
+) -> Option<Vec2> {
+    let cursor = window.cursor_position()?;
+    let window_size = Vec2::new(window.width(), window.height());
+    let normalized = cursor / window_size;
+
+    let view = Vec2::new(
+        projection.left + (projection.right - projection.left) * normalized.x,
+        projection.bottom + (projection.top - projection.bottom) * normalized.y,
+    ) * projection.scale;
+
+    Some(camera_transform.translation.truncate() + view)
+}
+
+impl ClickToMovePlugin {
+    fn click_to_move(
+        mut commands: Commands,
+        actions: Res<Actions>,
+        windows: Res<Windows>,
+        transition: Res<TransitionState>,
+        grid: Res<NavGrid>,
+        mut queries: ResMut<PathQueries>,
+        camera_query: Query<(&GlobalTransform, &OrthographicProjection), With<MainCamera>>,
+        player_query: Query<(Entity, &GlobalTransform, Option<&PathFollow>), With<Player>>,
+        ui_query: Query<&Interaction>,
+    ) {
+        if transition.is_active() || !actions.just_pressed(Action::MoveTo) {
+            return;
+        }
+
+        // the click is meant for the minimap or whatever other UI is under the cursor
+        if ui_query
+            .iter()
+            .any(|&interaction| interaction != Interaction::None)
+        {
+            return;
+        }
+
+        let goal = match (windows.get_primary(), camera_query.get_single()) {
+            (Some(window), Ok((camera_transform, projection))) => {
+                match cursor_world_position(window, camera_transform, projection) {
+                    Some(goal) => goal,
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+
+        if !grid.walkable(grid.cell_at(goal)) {
+            return;
+        }
+
+        for (player_entity, player_transform, follow) in player_query.iter() {
+            if let Some(query) = follow.and_then(|follow| follow.query) {
+                queries.cancel(query);
+            }
+
+            let from = player_transform.translation.truncate();
+            commands.entity(player_entity).insert(PathFollow {
+                goal,
+                query: Some(queries.request(&grid, from, goal)),
+                waypoints: Vec::new(),
+            });
+        }
+    }
+
+    /// Walls were added or removed, or levels came and went, so paths have to be searched again
+    fn replan_paths(
+        grid: Res<NavGrid>,
+        mut queries: ResMut<PathQueries>,
+        mut follower_query: Query<(&GlobalTransform, &mut PathFollow)>,
+    ) {
+        if !grid.is_changed() {
+            return;
+        }
+
+        for (transform, mut follow) in follower_query.iter_mut() {
+            if let Some(query) = follow.query {
+                queries.cancel(query);
+            }
+
+            let from = transform.translation.truncate();
+            follow.query = Some(queries.request(&grid, from, follow.goal));
+        }
+    }
+
+    fn follow_path(
+        mut commands: Commands,
+        actions: Res<Actions>,
+        transition: Res<TransitionState>,
+        mut queries: ResMut<PathQueries>,
+        mut follower_query: Query<(
+            Entity,
+            &GlobalTransform,
+            &mut MovementController,
+            &mut PathFollow,
+        )>,
+    ) {
+        // the movement input takes over again as soon as it is used
+        let cancelled = transition.is_active() || actions.movement() != Vec2::ZERO;
+
+        for (entity, transform, mut movement, mut follow) in follower_query.iter_mut() {
+            if let Some(query) = follow.query {
+                match queries.poll(query) {
+                    PathResult::Pending => (),
+                    PathResult::Found(waypoints) => {
+                        follow.query = None;
+                        follow.waypoints = waypoints;
+                    }
+                    PathResult::NotFound => {
+                        follow.query = None;
+                        follow.waypoints.clear();
+                    }
+                }
+            }
+
+            let position = transform.translation.truncate();
+
+            while let Some(&waypoint) = follow.waypoints.first() {
+                if position.distance(waypoint) > WAYPOINT_REACHED_DISTANCE {
+                    break;
+                }
+                follow.waypoints.remove(0);
+            }
+
+            let finished = follow.waypoints.is_empty() && follow.query.is_none();
+            if cancelled || finished {
+                if let Some(query) = follow.query {
+                    queries.cancel(query);
+                }
+
+                movement.0 = Vec2::ZERO;
+                commands.entity(entity).remove::<PathFollow>();
+                continue;
+            }
+
+            // stands still while the first path is searched
+            movement.0 = match follow.waypoints.first() {
+                Some(&waypoint) => (waypoint - position).normalize_or_zero(),
+                None => Vec2::ZERO,
+            };
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, reflect::TypeUuid};
+use heron::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    assets::{asset_loaded, AddRonAsset},
+    components::ColliderDefKey,
+};
+
+/// Loads collider definitions from `assets/entities.colliders.ron`
+/// and keeps the colliders of LDtk entities in sync with them
+pub struct ColliderPlugin;
+
+impl Plugin for ColliderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_ron_asset::<ColliderDefs>(&["colliders.ron"])
+            .add_startup_system(Self::load_defs)
+            .add_system(Self::apply_defs);
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub enum ColliderShapeDef {
+    Cuboid {
+        half_extends: Vec2,
+    },
+    Sphere {
+        radius: f32,
+    },
+    Capsule {
+        half_segment: f32,
+        radius: f32,
+    },
+    /// Cuboid as large as the LDtk entity, for entities resizable in LDtk
+    EntityCuboid,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub enum BodyDef {
+    Dynamic,
+    Static,
+    KinematicPositionBased,
+    KinematicVelocityBased,
+    Sensor,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ColliderDef {
+    pub shape: ColliderShapeDef,
+    pub body: BodyDef,
+    pub friction: f32,
+    pub density: f32,
+    pub restitution: f32,
+    pub lock_rotation: bool,
+}
+
+impl Default for ColliderDef {
+    fn default() -> Self {
+        let material = PhysicMaterial::default();
+
+        Self {
+            shape: ColliderShapeDef::Sphere { radius: 0.5 },
+            body: BodyDef::Dynamic,
+            friction: material.friction,
+            density: material.density,
+            restitution: material.restitution,
+            lock_rotation: true,
+        }
+    }
+}
+
+impl ColliderDef {
+    pub fn collision_shape(&self, entity_size: Vec2) -> CollisionShape {
+        match self.shape {
+            ColliderShapeDef::Cuboid { half_extends } => CollisionShape::Cuboid {
+                half_extends: half_extends.extend(0.),
+                border_radius: None,
+            },
+            ColliderShapeDef::Sphere { radius } => CollisionShape::Sphere { radius },
+            ColliderShapeDef::Capsule {
+                half_segment,
+                radius,
+            } => CollisionShape::Capsule {
+                half_segment,
+                radius,
+            },
+            ColliderShapeDef::EntityCuboid => CollisionShape::Cuboid {
+                half_extends: (entity_size / 2.).extend(0.),
+                border_radius: None,
+            },
+        }
+    }
+
+    pub fn rigid_body(&self) -> RigidBody {
+        match self.body {
+            BodyDef::Dynamic => RigidBody::Dynamic,
+            BodyDef::Static => RigidBody::Static,
+            BodyDef::KinematicPositionBased => RigidBody::KinematicPositionBased,
+            BodyDef::KinematicVelocityBased => RigidBody::KinematicVelocityBased,
+            BodyDef::Sensor => RigidBody::Sensor,
+        }
+    }
+
+    pub fn physic_material(&self) -> PhysicMaterial {
+        PhysicMaterial {
+            friction: self.friction,
+            density: self.density,
+            restitution: self.restitution,
+        }
+    }
+
+    pub fn rotation_constraints(&self) -> RotationConstraints {
+        if self.lock_rotation {
+            RotationConstraints::lock()
+        } else {
+            RotationConstraints::default()
+        }
+    }
+}
+
+/// Collider definitions keyed by LDtk entity identifier
+#[derive(Clone, Debug, Default, Deserialize, TypeUuid, Deref)]
+#[serde(transparent)]
+#[uuid = "5b1e8f0c-3d7a-4e2b-9c61-0f4a7d2e8b13"]
+pub struct ColliderDefs(HashMap<String, ColliderDef>);
+
+pub struct ColliderDefsHandle(pub Handle<ColliderDefs>);
+
+impl ColliderPlugin {
+    fn load_defs(mut commands: Commands, asset_server: Res<AssetServer>) {
+        commands.insert_resource(ColliderDefsHandle(
+            asset_server.load("entities.colliders.ron"),
+        ));
+    }
+
+    /// Inserts colliders for new entities, and for all of them when the definitions are (re)loaded
+    fn apply_defs(
+        mut commands: Commands,
+        mut asset_events: EventReader<AssetEvent<ColliderDefs>>,
+        handle: Res<ColliderDefsHandle>,
+        defs: Res<Assets<ColliderDefs>>,
+        all_query: Query<(Entity, &ColliderDefKey)>,
+        added_query: Query<(Entity, &ColliderDefKey), Added<ColliderDefKey>>,
+    ) {
+        let defs_changed = asset_loaded(&mut asset_events, &handle.0);
+
+        let defs = match defs.get(&handle.0) {
+            Some(defs) => defs,
+            None => return,
+        };
+
+        let apply = |(entity, key): (Entity, &ColliderDefKey)| {
+            if key.identifier.is_empty() {
+                return;
+            }
+
+            match defs.get(&key.identifier) {
+                Some(def) => {
+                    commands
+                        .entity(entity)
+                        .insert(def.collision_shape(key.entity_size))
+                        .insert(def.rigid_body())
+                        .insert(def.physic_material())
+                        .insert(def.rotation_constraints());
+                }
+                None => warn!("No collider definition for `{}`", key.identifier),
+            }
+        };
+
+        if defs_changed {
+            all_query.for_each(apply);
+        } else {
+            added_query.for_each(apply);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use heron::prelude::*;
+
+use crate::{components::*, plugins::Interacted, AppState};
+
+pub struct DoorPlugin;
+
+impl Plugin for DoorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::InGame)
+                .with_system(Self::use_doors)
+                .with_system(Self::update_doors),
+        )
+        .register_ldtk_entity::<DoorBundle>("Door");
+    }
+}
+
+impl DoorPlugin {
+    fn use_doors(
+        mut interacted_events: EventReader<Interacted>,
+        actor_query: Query<(&GlobalTransform, &Inventory)>,
+        mut door_query: Query<(&GlobalTransform, &mut Door)>,
+    ) {
+        for &Interacted { actor, target } in interacted_events.iter() {
+            let (door_transform, mut door) = match door_query.get_mut(target) {
+                Ok(door) => door,
+                Err(_) => continue,
+            };
+
+            let (actor_transform, inventory) = match actor_query.get(actor) {
+                Ok(actor) => actor,
+                Err(_) => continue,
+            };
+
+            if door.locked {
+                if inventory.contains(door.key) {
+                    door.locked = false;
+                } else {
+                    info!("The door is locked, {} is needed", door.key);
+                    continue;
+                }
+            }
+
+            // don't shut the door on the player standing in the doorway
+            let actor_position = actor_transform.translation.truncate();
+            let door_position = door_transform.translation.truncate();
+            let inside = ((actor_position - door_position).abs() - door.size / 2.)
+                .cmple(Vec2::ZERO)
+                .all();
+            if door.open && inside {
+                continue;
+            }
+
+            door.open = !door.open;
+        }
+    }
+
+    /// Swaps the sprite, the collider and the prompt of doors that were opened or closed
+    ///
+    /// The definition key goes with the collider, so reloading `entities.colliders.ron`
+    /// doesn't put the collider back into an open door.
+    fn update_doors(
+        mut commands: Commands,
+        mut door_query: Query<
+            (Entity, &Door, &mut TextureAtlasSprite, &mut Interactable),
+            Changed<Door>,
+        >,
+    ) {
+        for (entity, door, mut sprite, mut interactable) in door_query.iter_mut() {
+            interactable.prompt = if door.locked {
+                "interact.unlock_door"
+            } else if door.open {
+                "interact.close_door"
+            } else {
+                "interact.open_door"
+            }
+            .to_owned();
+
+            if door.open {
+                sprite.index = DOOR_OPENED_SPRITE;
+                commands
+                    .entity(entity)
+                    .remove::<ColliderDefKey>()
+                    .remove::<CollisionShape>();
+            } else {
+                sprite.index = DOOR_CLOSED_SPRITE;
+                // the ColliderPlugin inserts the collider of the new key
+                commands.entity(entity).insert(door.collider_def_key());
+            }
+        }
+    }
+}
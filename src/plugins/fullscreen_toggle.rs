@@ -1,29 +1,18 @@
 use bevy::{prelude::*, window::WindowMode::*};
 
+use crate::plugins::{Action, Actions};
+
 pub struct FullscreenTogglePlugin;
 
 impl Plugin for FullscreenTogglePlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(toggle_fullscreen)
-            .init_resource::<FullscreenToggleButton>();
-    }
-}
-
-pub struct FullscreenToggleButton(KeyCode);
-
-impl Default for FullscreenToggleButton {
-    fn default() -> Self {
-        Self(KeyCode::F11)
+        app.add_system(toggle_fullscreen);
     }
 }
 
-fn toggle_fullscreen(
-    input: Res<Input<KeyCode>>,
-    mut windows: ResMut<Windows>,
-    code: Res<FullscreenToggleButton>,
-) {
+fn toggle_fullscreen(actions: Res<Actions>, mut windows: ResMut<Windows>) {
     let window = windows.primary_mut();
-    if input.just_pressed(code.0) {
+    if actions.just_pressed(Action::ToggleFullscreen) {
         window.set_mode(
             vec![BorderlessFullscreen, Windowed][(window.mode() == BorderlessFullscreen) as usize],
         );
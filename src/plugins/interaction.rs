@@ -0,0 +1,193 @@
+use bevy::prelude::*;
+use heron::{prelude::*, rapier_plugin::PhysicsWorld};
+
+use crate::{
+    components::{CollisionLayer, Interactable, Interactor, Player},
+    plugins::{Action, Actions, TransitionState},
+    systems::MovementController,
+    AppState,
+};
+
+/// Picks the `Interactable` the player faces, shows its prompt over it,
+/// and sends `Interacted` when the interact action is pressed
+pub struct InteractionPlugin;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Interacted>()
+            .add_startup_system(Self::spawn_prompt)
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(Self::find_focus)
+                    .with_system(Self::interact)
+                    .with_system(Self::update_prompt),
+            );
+    }
+}
+
+/// Sent when `actor` uses `target`, the plugins of the target react to it
+#[derive(Copy, Clone, Debug)]
+pub struct Interacted {
+    pub actor: Entity,
+    pub target: Entity,
+}
+
+/// Floating text over the focused interactable
+#[derive(Component)]
+struct InteractionPrompt;
+
+/// How close the player has to be to use an object, in pixels
+const INTERACTION_DISTANCE: f32 = 24.;
+/// Cosine of the widest angle between the facing direction and an interactable in front
+const FACING_COS: f32 = 0.5;
+/// Interactables closer than this count as in front whatever the facing, in pixels
+const TOUCH_DISTANCE: f32 = 4.;
+/// Height of the prompt over the center of the interactable, in pixels
+const PROMPT_OFFSET: f32 = 20.;
+
+impl InteractionPlugin {
+    fn spawn_prompt(mut commands: Commands, asset_server: Res<AssetServer>) {
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::with_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 10.,
+                        color: Color::WHITE,
+                    },
+                    TextAlignment {
+                        vertical: VerticalAlign::Center,
+                        horizontal: HorizontalAlign::Center,
+                    },
+                ),
+                visibility: Visibility { is_visible: false },
+                ..default()
+            })
+            .insert(Name::new("InteractionPrompt"))
+            .insert(InteractionPrompt);
+    }
+
+    fn find_focus(
+        physics_world: PhysicsWorld,
+        mut actor_query: Query<(
+            Entity,
+            &GlobalTransform,
+            &MovementController,
+            &mut Interactor,
+        )>,
+        interactable_query: Query<(Entity, &GlobalTransform, &Interactable)>,
+    ) {
+        // only walls can stand between an actor and what it uses
+        let sight_layers = CollisionLayers::none()
+            .with_group(CollisionLayer::Player)
+            .with_mask(CollisionLayer::Wall);
+
+        for (actor, actor_transform, movement, mut interactor) in actor_query.iter_mut() {
+            if movement.0 != Vec2::ZERO {
+                interactor.facing = movement.0.normalize();
+            }
+
+            let position = actor_transform.translation.truncate();
+            let facing = interactor.facing;
+
+            let focus = interactable_query
+                .iter()
+                .filter(|(target, _, interactable)| interactable.enabled && *target != actor)
+                .filter_map(|(target, transform, interactable)| {
+                    let offset = transform.translation.truncate() - position;
+                    let distance = offset.length();
+                    if distance > INTERACTION_DISTANCE {
+                        return None;
+                    }
+
+                    if distance > TOUCH_DISTANCE && offset.dot(facing) / distance < FACING_COS {
+                        return None;
+                    }
+
+                    let blocked = physics_world
+                        .ray_cast_with_filter(
+                            position.extend(0.),
+                            offset.extend(0.),
+                            true,
+                            sight_layers,
+                            |hit| hit != actor && hit != target,
+                        )
+                        .is_some();
+                    if blocked {
+                        return None;
+                    }
+
+                    Some((interactable.priority, distance, target))
+                })
+                .max_by(|(a_priority, a_distance, _), (b_priority, b_distance, _)| {
+                    a_priority
+                        .cmp(b_priority)
+                        .then_with(|| b_distance.total_cmp(a_distance))
+                })
+                .map(|(.., target)| target);
+
+            if interactor.focus != focus {
+                interactor.focus = focus;
+            }
+        }
+    }
+
+    fn interact(
+        actions: Res<Actions>,
+        transition: Res<TransitionState>,
+        actor_query: Query<(Entity, &Interactor)>,
+        mut interacted_events: EventWriter<Interacted>,
+    ) {
+        if transition.is_active() || !actions.just_pressed(Action::Interact) {
+            return;
+        }
+
+        for (actor, interactor) in actor_query.iter() {
+            if let Some(target) = interactor.focus {
+                interacted_events.send(Interacted { actor, target });
+            }
+        }
+    }
+
+    fn update_prompt(
+        transition: Res<TransitionState>,
+        player_query: Query<&Interactor, With<Player>>,
+        interactable_query: Query<(&GlobalTransform, &Interactable)>,
+        mut prompt_query: Query<
+            (&mut Text, &mut Transform, &mut Visibility),
+            With<InteractionPrompt>,
+        >,
+    ) {
+        let (mut text, mut transform, mut visibility) = match prompt_query.get_single_mut() {
+            Ok(prompt) => prompt,
+            Err(_) => return,
+        };
+
+        let focused = player_query
+            .iter()
+            .find_map(|interactor| interactor.focus)
+            .and_then(|focus| interactable_query.get(focus).ok())
+            .filter(|_| !transition.is_active());
+
+        let (target_transform, interactable) = match focused {
+            Some(focused) => focused,
+            None => {
+                if visibility.is_visible {
+                    visibility.is_visible = false;
+                }
+                return;
+            }
+        };
+
+        let prompt = t!(&interactable.prompt);
+        if text.sections[0].value != prompt {
+            text.sections[0].value = prompt;
+        }
+
+        // over every sprite, whatever their sorting
+        transform.translation =
+            (target_transform.translation.truncate() + Vec2::new(0., PROMPT_OFFSET)).extend(100.);
+        visibility.is_visible = true;
+    }
+}
@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+
+use crate::components::{Inventory, InventoryChange, Item};
+
+pub struct InventoryPlugin;
+
+#[derive(Clone, Debug)]
+pub struct ItemAdded {
+    pub owner: Entity,
+    pub item: Item,
+}
+
+#[derive(Clone, Debug)]
+pub struct ItemRemoved {
+    pub owner: Entity,
+    pub item: Item,
+}
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ItemAdded>()
+            .add_event::<ItemRemoved>()
+            .add_system_to_stage(CoreStage::PostUpdate, Self::send_inventory_events);
+    }
+}
+
+impl InventoryPlugin {
+    fn send_inventory_events(
+        mut inventory_query: Query<(Entity, &mut Inventory), Changed<Inventory>>,
+        mut added_events: EventWriter<ItemAdded>,
+        mut removed_events: EventWriter<ItemRemoved>,
+    ) {
+        for (owner, mut inventory) in inventory_query.iter_mut() {
+            // draining marks the inventory as changed, so only do it when there is something to send
+            if !inventory.has_changes() {
+                continue;
+            }
+
+            for change in inventory.drain_changes() {
+                match change {
+                    InventoryChange::Added(item) => added_events.send(ItemAdded { owner, item }),
+                    InventoryChange::Removed(item) => {
+                        removed_events.send(ItemRemoved { owner, item })
+                    }
+                }
+            }
+        }
+    }
+}
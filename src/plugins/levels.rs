@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::components::LevelInfo;
+
+/// Keeps `LevelInfo` in sync with the `LevelSelection`
+pub struct LevelInfoPlugin;
+
+impl Plugin for LevelInfoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelInfo>()
+            .add_event::<LevelEntered>()
+            .add_system(Self::update_level_info);
+    }
+}
+
+/// Sent when another level gets selected, so UI and audio can react
+#[derive(Clone, Debug)]
+pub struct LevelEntered {
+    pub info: LevelInfo,
+}
+
+impl LevelInfoPlugin {
+    /// Reads the fields from the project, so the info is there before the level is spawned
+    fn update_level_info(
+        level_selection: Res<LevelSelection>,
+        world_query: Query<&Handle<LdtkAsset>>,
+        ldtk_assets: Res<Assets<LdtkAsset>>,
+        mut level_info: ResMut<LevelInfo>,
+        mut entered_events: EventWriter<LevelEntered>,
+    ) {
+        let level = world_query
+            .iter()
+            .filter_map(|handle| ldtk_assets.get(handle))
+            .flat_map(|ldtk_asset| ldtk_asset.project.levels.iter().enumerate())
+            .find(|(index, level)| level_selection.is_match(index, level));
+
+        if let Some((_, level)) = level {
+            if level.iid == level_info.iid {
+                return;
+            }
+
+            *level_info = LevelInfo::from(level);
+            entered_events.send(LevelEntered {
+                info: level_info.clone(),
+            });
+        }
+    }
+}
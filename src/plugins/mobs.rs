@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_inspector_egui::RegisterInspectable;
+use heron::prelude::*;
+
+use crate::{components::*, AppState};
+
+pub struct MobPlugin;
+
+impl Plugin for MobPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::InGame)
+                .with_system(Self::patrol)
+                .with_system(Self::animate_mobs),
+        )
+        .register_inspectable::<Patrol>()
+        .register_ldtk_entity::<MobBundle>("Mob");
+    }
+}
+
+impl MobPlugin {
+    /// Walks mobs along their patrol points, waiting `Patrol::wait` seconds at each of them
+    fn patrol(time: Res<Time>, mut query: Query<(&Transform, &mut Velocity, &mut Patrol)>) {
+        for (transform, mut velocity, mut patrol) in query.iter_mut() {
+            if patrol.points.len() < 2 {
+                velocity.linear = Vec3::ZERO;
+                continue;
+            }
+
+            if patrol.wait_left > 0. {
+                patrol.wait_left -= time.delta_seconds();
+                velocity.linear = Vec3::ZERO;
+                continue;
+            }
+
+            let offset = patrol.points[patrol.index] - transform.translation.truncate();
+
+            // stop before overshooting the point on the next frame
+            if offset.length() <= (patrol.speed * time.delta_seconds()).max(1.) {
+                patrol.wait_left = patrol.wait;
+                patrol.advance();
+                velocity.linear = Vec3::ZERO;
+            } else {
+                velocity.linear = (offset.normalize() * patrol.speed).extend(0.);
+            }
+        }
+    }
+
+    /// Faces mobs where they walk and plays their walk cycle, resting mobs keep facing that way
+    fn animate_mobs(
+        time: Res<Time>,
+        mut query: Query<(&Velocity, &mut MobAnimation, &mut TextureAtlasSprite)>,
+    ) {
+        for (velocity, mut animation, mut sprite) in query.iter_mut() {
+            animation.timer.tick(time.delta());
+            if !animation.timer.just_finished() {
+                continue;
+            }
+
+            let direction = velocity.linear.truncate();
+            animation.step = if direction == Vec2::ZERO {
+                0
+            } else {
+                (animation.step + 1) % MOB_WALK_CYCLE.len()
+            };
+
+            sprite.index =
+                MobAnimation::row_start(direction, sprite.index) + MOB_WALK_CYCLE[animation.step];
+        }
+    }
+}
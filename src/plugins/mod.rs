@@ -7,4 +7,25 @@ macro_rules! use_all {
     };
 }
 
-use_all!(fullscreen_toggle, fps_meter, sprite_sorting);
+use_all!(
+    actions,
+    fullscreen_toggle,
+    fps_meter,
+    camera,
+    camera_effects,
+    sprite_sorting,
+    chests,
+    mobs,
+    doors,
+    inventory,
+    levels,
+    colliders,
+    tiles,
+    travel,
+    transitions,
+    world_map,
+    navigation,
+    movement,
+    interaction,
+    click_to_move
+);
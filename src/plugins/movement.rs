@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::RegisterInspectable;
+
+use crate::{
+    assets::{asset_loaded, AddRonAsset},
+    components::{MovementTuning, Player, Stamina},
+    plugins::{Action, Actions},
+    systems::MovementController,
+    AppState,
+};
+
+/// Loads the movement tuning of the player from `assets/player.movement.ron`
+/// and runs its stamina while it sprints
+///
+/// The tuning can also be changed live in the inspector, until the file is saved again.
+pub struct MovementPlugin;
+
+impl Plugin for MovementPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_ron_asset::<MovementTuning>(&["movement.ron"])
+            .register_inspectable::<MovementTuning>()
+            .register_inspectable::<Stamina>()
+            .add_startup_system(Self::load_tuning)
+            .add_system(Self::apply_tuning)
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame).with_system(Self::update_stamina),
+            );
+    }
+}
+
+pub struct PlayerTuningHandle(pub Handle<MovementTuning>);
+
+impl MovementPlugin {
+    fn load_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+        commands.insert_resource(PlayerTuningHandle(asset_server.load("player.movement.ron")));
+    }
+
+    /// Copies the file onto new players, and onto every player after the file is saved
+    fn apply_tuning(
+        mut asset_events: EventReader<AssetEvent<MovementTuning>>,
+        handle: Res<PlayerTuningHandle>,
+        tunings: Res<Assets<MovementTuning>>,
+        mut player_query: Query<
+            (&mut MovementTuning, &mut Stamina, ChangeTrackers<Player>),
+            With<Player>,
+        >,
+    ) {
+        let tuning_changed = asset_loaded(&mut asset_events, &handle.0);
+
+        let loaded = tunings.get(&handle.0).copied();
+
+        for (mut tuning, mut stamina, player_tracker) in player_query.iter_mut() {
+            let is_new = player_tracker.is_added();
+
+            if let Some(loaded) = loaded {
+                if is_new || tuning_changed {
+                    *tuning = loaded;
+                }
+            }
+
+            // players spawned before the file loaded still start rested
+            if is_new {
+                stamina.current = tuning.max_stamina;
+            } else if stamina.current > tuning.max_stamina {
+                stamina.current = tuning.max_stamina;
+            }
+        }
+    }
+
+    fn update_stamina(
+        time: Res<Time>,
+        actions: Res<Actions>,
+        mut query: Query<(&MovementController, &MovementTuning, &mut Stamina), With<Player>>,
+    ) {
+        let delta = time.delta_seconds();
+
+        for (movement, tuning, mut stamina) in query.iter_mut() {
+            if stamina.exhausted && stamina.current >= tuning.max_stamina * tuning.sprint_recovery {
+                stamina.exhausted = false;
+            }
+
+            stamina.sprinting =
+                actions.pressed(Action::Sprint) && movement.0 != Vec2::ZERO && !stamina.exhausted;
+
+            if stamina.sprinting {
+                stamina.current = (stamina.current - tuning.stamina_drain * delta).max(0.);
+                if stamina.current <= 0. {
+                    stamina.exhausted = true;
+                }
+            } else {
+                stamina.current =
+                    (stamina.current + tuning.stamina_regen * delta).min(tuning.max_stamina);
+            }
+        }
+    }
+}
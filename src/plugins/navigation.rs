@@ -0,0 +1,340 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    components::{Door, LevelTiles, LevelWalls, TileBehavior, WallSettings},
+    pathfinding::{Search, SearchState},
+    AppState,
+};
+
+/// Keeps a `NavGrid` of all loaded levels and answers `PathQueries` with a few cells every frame
+///
+/// Anything that has to walk around walls, like mobs, NPCs or the clicked path of the player,
+/// asks for a path with `PathQueries::request` and polls for it in the next frames.
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavGrid>()
+            .init_resource::<PathQueries>()
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(Self::update_nav_grid)
+                    .with_system(Self::update_nav_doors)
+                    .with_system(Self::run_path_queries),
+            );
+    }
+}
+
+/// Cells of one level, in world cells
+#[derive(Clone, Debug)]
+struct NavLevel {
+    min: IVec2,
+    size: IVec2,
+    /// Walls and water
+    blocked: HashSet<IVec2>,
+}
+
+impl NavLevel {
+    fn contains(&self, cell: IVec2) -> bool {
+        let local = cell - self.min;
+        local.x >= 0 && local.y >= 0 && local.x < self.size.x && local.y < self.size.y
+    }
+}
+
+/// Walkable cells of every loaded level, in one grid of world cells
+///
+/// World cell `(0, 0)` spans from the world origin to `grid_size` pixels up and right,
+/// so neighbor levels loaded with `load_level_neighbors` join up into a single grid.
+/// Cells outside of the loaded levels, walls, water and cells touched by closed doors
+/// are never walkable.
+#[derive(Clone, Debug, Default)]
+pub struct NavGrid {
+    grid_size: i32,
+    levels: HashMap<Entity, NavLevel>,
+    /// World rects of closed doors, as bottom left and top right corners
+    closed_doors: HashMap<Entity, (Vec2, Vec2)>,
+}
+
+impl NavGrid {
+    /// Size of a cell in pixels, taken from the first level added
+    pub fn grid_size(&self) -> i32 {
+        self.grid_size
+    }
+
+    pub fn cell_at(&self, position: Vec2) -> IVec2 {
+        (position / self.grid_size.max(1) as f32).floor().as_ivec2()
+    }
+
+    pub fn cell_center(&self, cell: IVec2) -> Vec2 {
+        (cell.as_vec2() + Vec2::splat(0.5)) * self.grid_size as f32
+    }
+
+    pub fn walkable(&self, cell: IVec2) -> bool {
+        let cell_min = cell.as_vec2() * self.grid_size as f32;
+        let cell_max = cell_min + Vec2::splat(self.grid_size as f32);
+
+        self.levels
+            .values()
+            .any(|level| level.contains(cell) && !level.blocked.contains(&cell))
+            && !self.closed_doors.values().any(|&(door_min, door_max)| {
+                door_min.cmplt(cell_max).all() && cell_min.cmplt(door_max).all()
+            })
+    }
+
+    /// Adds or replaces the cells of a level whose bottom left corner is at `translation`
+    fn insert_level(
+        &mut self,
+        level_entity: Entity,
+        translation: Vec2,
+        walls: &LevelWalls,
+        tiles: Option<&LevelTiles>,
+    ) {
+        if self.levels.is_empty() {
+            self.grid_size = walls.grid_size;
+        }
+
+        let origin = translation.round().as_ivec2();
+        let grid_size = self.grid_size;
+
+        if walls.grid_size != grid_size || origin.x % grid_size != 0 || origin.y % grid_size != 0 {
+            warn!(
+                "{:?} doesn't line up with the {}px navigation grid, nothing can path through it",
+                level_entity, grid_size
+            );
+            self.levels.remove(&level_entity);
+            return;
+        }
+
+        let min = IVec2::new(
+            origin.x.div_euclid(grid_size),
+            origin.y.div_euclid(grid_size),
+        );
+
+        let water = tiles
+            .and_then(|tiles| tiles.cells.get(&TileBehavior::Water))
+            .into_iter()
+            .flatten();
+
+        self.levels.insert(
+            level_entity,
+            NavLevel {
+                min,
+                size: IVec2::new(walls.width, walls.height),
+                blocked: walls
+                    .cells
+                    .iter()
+                    .chain(water)
+                    .map(|coords| min + IVec2::new(coords.x, coords.y))
+                    .collect(),
+            },
+        );
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct PathQueryId(u64);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathResult {
+    Pending,
+    /// World positions to walk through, ending at the goal
+    Found(Vec<Vec2>),
+    /// The goal is in a wall, outside of the loaded levels or walled off
+    NotFound,
+}
+
+#[derive(Clone, Debug)]
+struct PathQuery {
+    id: PathQueryId,
+    goal: Vec2,
+    search: Search,
+}
+
+/// Path searches shared by everything that walks, run a few cells at a time
+pub struct PathQueries {
+    /// Cells expanded per frame over all the searches
+    pub budget: usize,
+    next_id: u64,
+    pending: VecDeque<PathQuery>,
+    finished: HashMap<PathQueryId, Option<Vec<Vec2>>>,
+}
+
+impl Default for PathQueries {
+    fn default() -> Self {
+        Self {
+            budget: 2000,
+            next_id: 0,
+            pending: VecDeque::new(),
+            finished: HashMap::new(),
+        }
+    }
+}
+
+impl PathQueries {
+    /// Starts a search from `from` to `goal`, results come in the order they were requested
+    pub fn request(&mut self, grid: &NavGrid, from: Vec2, goal: Vec2) -> PathQueryId {
+        let id = PathQueryId(self.next_id);
+        self.next_id += 1;
+
+        self.pending.push_back(PathQuery {
+            id,
+            goal,
+            search: Search::new(grid.cell_at(from), grid.cell_at(goal)),
+        });
+
+        id
+    }
+
+    /// A finished result is handed out only once, unknown queries are `NotFound`
+    pub fn poll(&mut self, id: PathQueryId) -> PathResult {
+        match self.finished.remove(&id) {
+            Some(Some(waypoints)) => PathResult::Found(waypoints),
+            Some(None) => PathResult::NotFound,
+            None if self.pending.iter().any(|query| query.id == id) => PathResult::Pending,
+            None => PathResult::NotFound,
+        }
+    }
+
+    /// Has to be called for queries that won't be polled anymore, or their results stay around
+    pub fn cancel(&mut self, id: PathQueryId) {
+        self.pending.retain(|query| query.id != id);
+        self.finished.remove(&id);
+    }
+}
+
+impl NavigationPlugin {
+    fn update_nav_grid(
+        mut grid: ResMut<NavGrid>,
+        levels: Res<Assets<LdtkLevel>>,
+        settings: Res<WallSettings>,
+        changed_level_query: Query<
+            (
+                Entity,
+                &Handle<LdtkLevel>,
+                &GlobalTransform,
+                Option<&LevelWalls>,
+                Option<&LevelTiles>,
+            ),
+            Or<(
+                Changed<LevelWalls>,
+                Changed<LevelTiles>,
+                Changed<GlobalTransform>,
+            )>,
+        >,
+        level_query: Query<(), With<Handle<LdtkLevel>>>,
+    ) {
+        if grid
+            .levels
+            .keys()
+            .any(|&level_entity| level_query.get(level_entity).is_err())
+        {
+            grid.levels
+                .retain(|&level_entity, _| level_query.get(level_entity).is_ok());
+        }
+
+        for (level_entity, level_handle, transform, walls, tiles) in changed_level_query.iter() {
+            // levels without a single wall have no LevelWalls, they are walkable all over
+            let no_walls;
+            let walls = match walls {
+                Some(walls) => walls,
+                None => {
+                    let level = match levels.get(level_handle) {
+                        Some(level) => level,
+                        None => continue,
+                    };
+
+                    no_walls = match LevelWalls::for_level(level, &settings.layer_identifier) {
+                        Ok(no_walls) => no_walls,
+                        Err(error) => {
+                            warn!("Nothing can path through {:?}: {}", level_entity, error);
+                            continue;
+                        }
+                    };
+                    &no_walls
+                }
+            };
+
+            grid.insert_level(level_entity, transform.translation.truncate(), walls, tiles);
+        }
+    }
+
+    /// Closed and locked doors block the cells they touch, opening one replans the paths
+    fn update_nav_doors(
+        mut grid: ResMut<NavGrid>,
+        changed_door_query: Query<
+            (Entity, &GlobalTransform, &Door),
+            Or<(Changed<Door>, Changed<GlobalTransform>)>,
+        >,
+        door_query: Query<(), With<Door>>,
+    ) {
+        if grid
+            .closed_doors
+            .keys()
+            .any(|&door_entity| door_query.get(door_entity).is_err())
+        {
+            grid.closed_doors
+                .retain(|&door_entity, _| door_query.get(door_entity).is_ok());
+        }
+
+        for (door_entity, transform, door) in changed_door_query.iter() {
+            let center = transform.translation.truncate();
+            let rect = (!door.open).then(|| (center - door.size / 2., center + door.size / 2.));
+
+            if grid.closed_doors.get(&door_entity).copied() == rect {
+                continue;
+            }
+
+            match rect {
+                Some(rect) => grid.closed_doors.insert(door_entity, rect),
+                None => grid.closed_doors.remove(&door_entity),
+            };
+        }
+    }
+
+    fn run_path_queries(grid: Res<NavGrid>, mut queries: ResMut<PathQueries>) {
+        let queries = &mut *queries;
+
+        if grid.is_changed() {
+            for query in queries.pending.iter_mut() {
+                query.search.restart();
+            }
+        }
+
+        let mut budget = queries.budget;
+        while budget > 0 {
+            let mut query = match queries.pending.pop_front() {
+                Some(query) => query,
+                None => break,
+            };
+
+            let waypoints = match query.search.step(&mut budget, |cell| grid.walkable(cell)) {
+                SearchState::Searching => {
+                    // the oldest search goes on first in the next frame
+                    queries.pending.push_front(query);
+                    break;
+                }
+                SearchState::Found(cells) => {
+                    let mut waypoints: Vec<Vec2> = cells
+                        .into_iter()
+                        .skip(1)
+                        .map(|cell| grid.cell_center(cell))
+                        .collect();
+
+                    // the last cell is walked all the way to the goal
+                    match waypoints.last_mut() {
+                        Some(last) => *last = query.goal,
+                        None => waypoints.push(query.goal),
+                    }
+
+                    Some(waypoints)
+                }
+                SearchState::NotFound => None,
+            };
+
+            queries.finished.insert(query.id, waypoints);
+        }
+    }
+}
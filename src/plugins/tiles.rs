@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use heron::prelude::*;
+
+use crate::{components::*, wall_merging::merge_wall_rects, AppState};
+
+pub struct TilePlugin;
+
+/// Sent when an actor steps on the first tile with some behavior
+#[derive(Clone, Debug)]
+pub struct TileEntered {
+    pub actor: Entity,
+    pub behavior: TileBehavior,
+}
+
+/// Sent when an actor leaves the last tile with some behavior
+#[derive(Clone, Debug)]
+pub struct TileExited {
+    pub actor: Entity,
+    pub behavior: TileBehavior,
+}
+
+#[derive(Clone, Debug)]
+pub struct HazardDamaged {
+    pub actor: Entity,
+    pub damage: f32,
+}
+
+pub struct TileSettings {
+    /// Speed multiplier on slow tiles
+    pub slow_factor: f32,
+    pub hazard_damage: f32,
+    /// Seconds between two hits of a hazard
+    pub hazard_interval: f32,
+}
+
+impl Default for TileSettings {
+    fn default() -> Self {
+        Self {
+            slow_factor: 0.5,
+            hazard_damage: 10.,
+            hazard_interval: 0.75,
+        }
+    }
+}
+
+impl Plugin for TilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TileEntered>()
+            .add_event::<TileExited>()
+            .add_event::<HazardDamaged>()
+            .init_resource::<TileSettings>()
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(Self::collect_tiles)
+                    .with_system(Self::spawn_tile_collision)
+                    .with_system(Self::track_tile_contacts)
+                    .with_system(Self::forget_despawned_tiles)
+                    .with_system(Self::hurt_on_hazards),
+            );
+
+        // special tiles share the layer of the walls
+        let layer_identifier = app
+            .world
+            .get_resource_or_insert_with(WallSettings::default)
+            .layer_identifier
+            .clone();
+
+        for (value, _) in TILE_BEHAVIORS {
+            app.register_ldtk_int_cell_for_layer::<TileBundle>(&layer_identifier, value);
+        }
+    }
+}
+
+impl TilePlugin {
+    /// Gathers new special tile cells into the `LevelTiles` of their levels
+    fn collect_tiles(
+        mut commands: Commands,
+        tile_query: Query<(&GridCoords, &TileBehavior, &Parent), Added<TileBehavior>>,
+        parent_query: Query<&Parent, Without<TileBehavior>>,
+        mut level_query: Query<(&Handle<LdtkLevel>, Option<&mut LevelTiles>)>,
+        levels: Res<Assets<LdtkLevel>>,
+        settings: Res<WallSettings>,
+    ) {
+        let mut level_to_tiles: HashMap<Entity, Vec<(TileBehavior, GridCoords)>> = HashMap::new();
+
+        tile_query.for_each(|(&grid_coords, &behavior, &Parent(parent))| {
+            // like wall cells, tile cells are children of tilemap chunks, not of the level
+            if let Ok(&Parent(level_entity)) = parent_query.get(parent) {
+                level_to_tiles
+                    .entry(level_entity)
+                    .or_default()
+                    .push((behavior, grid_coords));
+            }
+        });
+
+        for (level_entity, tiles) in level_to_tiles {
+            let (level_handle, level_tiles) = match level_query.get_mut(level_entity) {
+                Ok(level) => level,
+                Err(_) => continue,
+            };
+
+            if let Some(mut level_tiles) = level_tiles {
+                for (behavior, coords) in tiles {
+                    level_tiles.insert(behavior, coords);
+                }
+                continue;
+            }
+
+            let level = match levels.get(level_handle) {
+                Some(level) => level,
+                None => {
+                    error!(
+                        "Tiles of {:?} spawned before its level was loaded",
+                        level_entity
+                    );
+                    continue;
+                }
+            };
+
+            match LevelTiles::for_level(level, &settings.layer_identifier) {
+                Ok(mut level_tiles) => {
+                    for (behavior, coords) in tiles {
+                        level_tiles.insert(behavior, coords);
+                    }
+                    commands.entity(level_entity).insert(level_tiles);
+                }
+                Err(error) => error!("{}", error),
+            }
+        }
+    }
+
+    /// Merges the special tiles of every behavior into rectangle colliders, like the walls
+    fn spawn_tile_collision(
+        mut commands: Commands,
+        level_query: Query<(Entity, &LevelTiles), Changed<LevelTiles>>,
+        collider_query: Query<(Entity, &Parent), With<TileCollider>>,
+    ) {
+        for (level_entity, level_tiles) in level_query.iter() {
+            for (collider, &Parent(parent)) in collider_query.iter() {
+                if parent == level_entity {
+                    commands.entity(collider).despawn_recursive();
+                }
+            }
+
+            let grid_size = level_tiles.grid_size as f32;
+
+            for (&behavior, cells) in level_tiles.cells.iter() {
+                for rect in merge_wall_rects(cells, level_tiles.width, level_tiles.height) {
+                    let size = Vec2::new(
+                        (rect.right - rect.left + 1) as f32,
+                        (rect.top - rect.bottom + 1) as f32,
+                    ) * grid_size;
+                    let center = Vec2::new(
+                        (rect.left + rect.right + 1) as f32,
+                        (rect.bottom + rect.top + 1) as f32,
+                    ) * grid_size
+                        / 2.;
+
+                    commands
+                        .spawn()
+                        .insert(CollisionShape::Cuboid {
+                            half_extends: (size / 2.).extend(0.),
+                            border_radius: None,
+                        })
+                        .insert(behavior.rigid_body())
+                        .insert(CollisionLayer::for_tile(behavior).collision_layers())
+                        .insert(Transform::from_translation(center.extend(0.)))
+                        .insert(GlobalTransform::default())
+                        .insert(behavior)
+                        .insert(TileCollider)
+                        // relative to the level and despawned with it, like the wall colliders
+                        .insert(Parent(level_entity));
+                }
+            }
+        }
+    }
+
+    fn track_tile_contacts(
+        mut collision_events: EventReader<CollisionEvent>,
+        tile_query: Query<&TileBehavior>,
+        mut actor_query: Query<&mut TileContacts>,
+        mut entered_events: EventWriter<TileEntered>,
+        mut exited_events: EventWriter<TileExited>,
+    ) {
+        for event in collision_events.iter() {
+            let (a, b) = event.rigid_body_entities();
+
+            for (actor, tile) in [(a, b), (b, a)] {
+                if let (Ok(mut contacts), Ok(&behavior)) =
+                    (actor_query.get_mut(actor), tile_query.get(tile))
+                {
+                    match event {
+                        CollisionEvent::Started(..) => {
+                            if contacts.insert(tile, behavior) {
+                                entered_events.send(TileEntered { actor, behavior });
+                            }
+                        }
+                        CollisionEvent::Stopped(..) => {
+                            if let Some(behavior) = contacts.remove(tile) {
+                                exited_events.send(TileExited { actor, behavior });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Tiles of unloaded levels don't send `CollisionEvent::Stopped`
+    fn forget_despawned_tiles(
+        mut actor_query: Query<(Entity, &mut TileContacts)>,
+        tile_query: Query<(), With<TileBehavior>>,
+        mut exited_events: EventWriter<TileExited>,
+    ) {
+        for (actor, mut contacts) in actor_query.iter_mut() {
+            let despawned: Vec<Entity> = contacts
+                .tiles()
+                .filter(|&tile| tile_query.get(tile).is_err())
+                .collect();
+
+            for tile in despawned {
+                if let Some(behavior) = contacts.remove(tile) {
+                    exited_events.send(TileExited { actor, behavior });
+                }
+            }
+        }
+    }
+
+    fn hurt_on_hazards(
+        time: Res<Time>,
+        settings: Res<TileSettings>,
+        mut actor_query: Query<(Entity, &mut TileContacts, Option<&mut Health>)>,
+        mut damaged_events: EventWriter<HazardDamaged>,
+    ) {
+        for (actor, mut contacts, health) in actor_query.iter_mut() {
+            if !contacts.touches(TileBehavior::Hazard) {
+                if contacts.hazard_cooldown > 0. {
+                    contacts.hazard_cooldown = 0.;
+                }
+                continue;
+            }
+
+            contacts.hazard_cooldown -= time.delta_seconds();
+            if contacts.hazard_cooldown > 0. {
+                continue;
+            }
+
+            contacts.hazard_cooldown = settings.hazard_interval;
+            if let Some(mut health) = health {
+                health.damage(settings.hazard_damage);
+            }
+
+            damaged_events.send(HazardDamaged {
+                actor,
+                damage: settings.hazard_damage,
+            });
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use heron::prelude::*;
+
+use crate::{components::*, AppState};
+
+/// Moves the player through `Transition` entities, hiding the level load behind a fade
+pub struct TransitionPlugin;
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TransitionState>()
+            .add_startup_system(Self::spawn_fade_overlay)
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(Self::enter_transitions)
+                    .with_system(Self::run_transition)
+                    .with_system(Self::update_fade_overlay),
+            )
+            .register_ldtk_entity::<TransitionBundle>("Transition");
+    }
+}
+
+/// Seconds the screen takes to fade out, and then back in
+pub const FADE_DURATION: f32 = 0.3;
+
+#[derive(Clone, Debug)]
+enum TransitionPhase {
+    Idle,
+    FadingOut(TransitionTarget),
+    /// Waiting for `LevelEvent::Transformed` of the target level
+    Loading(String),
+    FadingIn,
+}
+
+/// Progress of the current transition, player input is frozen while one is active
+pub struct TransitionState {
+    phase: TransitionPhase,
+    /// Opacity of the fade overlay
+    fade: f32,
+}
+
+impl Default for TransitionState {
+    fn default() -> Self {
+        Self {
+            phase: TransitionPhase::Idle,
+            fade: 0.,
+        }
+    }
+}
+
+impl TransitionState {
+    pub fn is_active(&self) -> bool {
+        !matches!(self.phase, TransitionPhase::Idle)
+    }
+}
+
+#[derive(Component)]
+struct FadeOverlay;
+
+impl TransitionPlugin {
+    fn spawn_fade_overlay(mut commands: Commands) {
+        commands
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        left: Val::Px(0.),
+                        bottom: Val::Px(0.),
+                        ..default()
+                    },
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    ..default()
+                },
+                color: Color::NONE.into(),
+                ..default()
+            })
+            .insert(Name::new("FadeOverlay"))
+            .insert(FadeOverlay);
+    }
+
+    fn enter_transitions(
+        mut collision_events: EventReader<CollisionEvent>,
+        mut state: ResMut<TransitionState>,
+        player_query: Query<(), With<Player>>,
+        transition_query: Query<&Transition>,
+    ) {
+        for event in collision_events.iter() {
+            if state.is_active() {
+                continue;
+            }
+
+            if let CollisionEvent::Started(..) = event {
+                let (a, b) = event.rigid_body_entities();
+
+                for (player, transition) in [(a, b), (b, a)] {
+                    if let (Ok(()), Ok(transition)) =
+                        (player_query.get(player), transition_query.get(transition))
+                    {
+                        if let Some(target) = &transition.target {
+                            state.phase = TransitionPhase::FadingOut(target.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn run_transition(
+        time: Res<Time>,
+        mut state: ResMut<TransitionState>,
+        mut level_events: EventReader<LevelEvent>,
+        mut level_selection: ResMut<LevelSelection>,
+        world_query: Query<&Handle<LdtkAsset>>,
+        level_query: Query<&Handle<LdtkLevel>>,
+        mut player_query: Query<&mut Transform, With<Player>>,
+        ldtk_assets: Res<Assets<LdtkAsset>>,
+        ldtk_levels: Res<Assets<LdtkLevel>>,
+    ) {
+        let step = time.delta_seconds() / FADE_DURATION;
+
+        let transformed: Vec<String> = level_events
+            .iter()
+            .filter_map(|event| match event {
+                LevelEvent::Transformed(iid) => Some(iid.clone()),
+                _ => None,
+            })
+            .collect();
+
+        match state.phase.clone() {
+            TransitionPhase::Idle => (),
+            TransitionPhase::FadingOut(target) => {
+                state.fade = (state.fade + step).min(1.);
+                if state.fade < 1. {
+                    return;
+                }
+
+                let position = world_query
+                    .iter()
+                    .find_map(|handle| ldtk_assets.get(handle))
+                    .and_then(|ldtk_asset| target.world_position(ldtk_asset));
+
+                let position = match position {
+                    Some(position) => position,
+                    None => {
+                        error!(
+                            "Spawn `{}` of level `{}` doesn't exist",
+                            target.entity_iid, target.level_iid
+                        );
+                        state.phase = TransitionPhase::FadingIn;
+                        return;
+                    }
+                };
+
+                for mut transform in player_query.iter_mut() {
+                    transform.translation.x = position.x;
+                    transform.translation.y = position.y;
+                }
+
+                let level_spawned = level_query
+                    .iter()
+                    .filter_map(|handle| ldtk_levels.get(handle))
+                    .any(|ldtk_level| ldtk_level.level.iid == target.level_iid);
+
+                *level_selection = LevelSelection::Iid(target.level_iid.clone());
+                state.phase = if level_spawned {
+                    TransitionPhase::FadingIn
+                } else {
+                    TransitionPhase::Loading(target.level_iid)
+                };
+            }
+            TransitionPhase::Loading(level_iid) => {
+                if transformed.contains(&level_iid) {
+                    state.phase = TransitionPhase::FadingIn;
+                }
+            }
+            TransitionPhase::FadingIn => {
+                state.fade = (state.fade - step).max(0.);
+                if state.fade <= 0. {
+                    state.phase = TransitionPhase::Idle;
+                }
+            }
+        }
+    }
+
+    fn update_fade_overlay(
+        state: Res<TransitionState>,
+        mut overlay_query: Query<&mut UiColor, With<FadeOverlay>>,
+    ) {
+        if !state.is_changed() {
+            return;
+        }
+
+        for mut color in overlay_query.iter_mut() {
+            color.0 = Color::rgba(0., 0., 0., state.fade);
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_ecs_ldtk::prelude::*;
+use serde::Deserialize;
+
+use crate::{assets::AddRonAsset, components::Player, plugins::SpriteSort};
+
+/// Loads the map registry from `assets/maps/world.maps.ron`
+/// and moves the player between LDtk files on `TravelTo`
+pub struct TravelPlugin;
+
+impl Plugin for TravelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_ron_asset::<MapRegistry>(&["maps.ron"])
+            .add_event::<TravelTo>()
+            .init_resource::<CurrentMap>()
+            .add_startup_system(Self::load_registry)
+            .add_system(Self::start_game)
+            .add_system(Self::travel)
+            .add_system(Self::place_player)
+            .add_system(Self::keep_traveler);
+    }
+}
+
+/// Moves the player to a named spawn point, loading another map if needed
+#[derive(Clone, Debug, Deserialize)]
+pub struct TravelTo {
+    pub map: String,
+    pub level: String,
+    pub spawn: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct MapDef {
+    /// Path of the `.ldtk` file, relative to `assets`
+    pub path: String,
+    /// Spawn points by level identifier and name, in LDtk pixel coordinates of the level
+    #[serde(default)]
+    pub spawns: HashMap<String, HashMap<String, IVec2>>,
+}
+
+impl MapDef {
+    pub fn spawn(&self, level: &str, spawn: &str) -> Option<IVec2> {
+        self.spawns.get(level)?.get(spawn).copied()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "5b4b1cf7-9ee9-4aa8-a63b-9f457e32c1de"]
+pub struct MapRegistry {
+    /// Where a new game begins
+    pub start: TravelTo,
+    pub maps: HashMap<String, MapDef>,
+}
+
+pub struct MapRegistryHandle(pub Handle<MapRegistry>);
+
+/// Name of the loaded map in the registry
+#[derive(Default)]
+pub struct CurrentMap(pub Option<String>);
+
+/// Exists while the player waits for the level of its spawn point to load
+pub struct PendingSpawn {
+    pub level: String,
+    pub position: IVec2,
+}
+
+/// The player that survived unloading of its map
+///
+/// Players spawned by the next map are dropped in favour of it.
+#[derive(Component)]
+pub struct Traveler;
+
+impl TravelPlugin {
+    fn load_registry(mut commands: Commands, asset_server: Res<AssetServer>) {
+        commands.insert_resource(MapRegistryHandle(asset_server.load("maps/world.maps.ron")));
+    }
+
+    fn start_game(
+        current_map: Res<CurrentMap>,
+        handle: Res<MapRegistryHandle>,
+        registries: Res<Assets<MapRegistry>>,
+        mut travel_events: EventWriter<TravelTo>,
+        mut started: Local<bool>,
+    ) {
+        if *started || current_map.0.is_some() {
+            return;
+        }
+
+        if let Some(registry) = registries.get(&handle.0) {
+            travel_events.send(registry.start.clone());
+            *started = true;
+        }
+    }
+
+    fn travel(
+        mut commands: Commands,
+        asset_server: Res<AssetServer>,
+        handle: Res<MapRegistryHandle>,
+        registries: Res<Assets<MapRegistry>>,
+        mut current_map: ResMut<CurrentMap>,
+        mut level_selection: ResMut<LevelSelection>,
+        mut travel_events: EventReader<TravelTo>,
+        world_query: Query<Entity, With<Handle<LdtkAsset>>>,
+        player_query: Query<(Entity, Option<&Parent>), With<Player>>,
+    ) {
+        // only the last request of a frame matters
+        let event = match travel_events.iter().last() {
+            Some(event) => event,
+            None => return,
+        };
+
+        let registry = match registries.get(&handle.0) {
+            Some(registry) => registry,
+            None => {
+                error!("Can't travel to `{}`, map registry isn't loaded", event.map);
+                return;
+            }
+        };
+
+        let map = match registry.maps.get(&event.map) {
+            Some(map) => map,
+            None => {
+                error!("No map `{}` in the map registry", event.map);
+                return;
+            }
+        };
+
+        let position = match map.spawn(&event.level, &event.spawn) {
+            Some(position) => position,
+            None => {
+                error!(
+                    "No spawn point `{}` in level `{}` of map `{}`",
+                    event.spawn, event.level, event.map
+                );
+                return;
+            }
+        };
+
+        if current_map.0.as_ref() != Some(&event.map) {
+            // keep the player, its inventory and health, while the old world is unloaded
+            for (player, parent) in player_query.iter() {
+                if let Some(&Parent(world)) = parent {
+                    commands.entity(world).remove_children(&[player]);
+                }
+                commands.entity(player).insert(Traveler);
+            }
+
+            for world in world_query.iter() {
+                commands.entity(world).despawn_recursive();
+            }
+
+            commands
+                .spawn_bundle(LdtkWorldBundle {
+                    ldtk_handle: asset_server.load(&map.path),
+                    ..Default::default()
+                })
+                .insert(Name::new("Map"))
+                .insert(SpriteSort::default());
+
+            current_map.0 = Some(event.map.clone());
+        }
+
+        *level_selection = LevelSelection::Identifier(event.level.clone());
+        commands.insert_resource(PendingSpawn {
+            level: event.level.clone(),
+            position,
+        });
+    }
+
+    /// Puts the player at the pending spawn point as soon as its level is spawned
+    fn place_player(
+        mut commands: Commands,
+        pending_spawn: Option<Res<PendingSpawn>>,
+        level_query: Query<(&Handle<LdtkLevel>, &Transform), Without<Player>>,
+        mut player_query: Query<&mut Transform, With<Player>>,
+        ldtk_levels: Res<Assets<LdtkLevel>>,
+    ) {
+        let pending_spawn = match pending_spawn {
+            Some(pending_spawn) => pending_spawn,
+            None => return,
+        };
+
+        let level = level_query.iter().find_map(|(handle, transform)| {
+            ldtk_levels
+                .get(handle)
+                .filter(|ldtk_level| ldtk_level.level.identifier == pending_spawn.level)
+                .map(|ldtk_level| (ldtk_level, transform))
+        });
+
+        let (ldtk_level, level_transform) = match level {
+            Some(level) => level,
+            None => return,
+        };
+
+        if player_query.is_empty() {
+            return;
+        }
+
+        // LDtk pixel coordinates go down from the top left corner
+        let position = level_transform.translation.truncate()
+            + Vec2::new(
+                pending_spawn.position.x as f32,
+                (ldtk_level.level.px_hei - pending_spawn.position.y) as f32,
+            );
+
+        for mut transform in player_query.iter_mut() {
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+
+        commands.remove_resource::<PendingSpawn>();
+    }
+
+    fn keep_traveler(
+        mut commands: Commands,
+        traveler_query: Query<(), With<Traveler>>,
+        added_query: Query<Entity, (Added<Player>, Without<Traveler>)>,
+    ) {
+        if traveler_query.is_empty() {
+            return;
+        }
+
+        for player in added_query.iter() {
+            commands.entity(player).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,495 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    components::{LevelInfo, LevelWalls, Player, WallSettings, WALL_INT_GRID_VALUE},
+    plugins::{Action, Actions, LevelEntered},
+    AppState,
+};
+
+/// Minimap in the corner of the screen and a full-screen world map,
+/// both drawn from the levels and walls of the LDtk project
+pub struct WorldMapPlugin;
+
+impl Plugin for WorldMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldMap>()
+            .init_resource::<VisitedLevels>()
+            .add_system(Self::track_visited_levels)
+            .add_system(Self::rebuild_map_image)
+            .add_system(Self::redraw_edited_walls)
+            .add_system_set(SystemSet::on_enter(AppState::InGame).with_system(Self::spawn_map_ui))
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(Self::toggle_world_map)
+                    .with_system(Self::update_minimap)
+                    .with_system(Self::update_world_map),
+            );
+    }
+}
+
+/// Iids of the levels the player has been to
+#[derive(Clone, Debug, Default)]
+pub struct VisitedLevels(pub HashSet<String>);
+
+/// Image of the whole world with one pixel per grid cell
+#[derive(Clone, Debug, Default)]
+pub struct WorldMap {
+    pub image: Handle<Image>,
+    /// Top left corner of the world, in LDtk pixel coordinates
+    pub origin: IVec2,
+    /// Size of the image in cells
+    pub size: UVec2,
+    pub grid_size: i32,
+}
+
+impl WorldMap {
+    /// Position of a point of the world on the map, from 0 to 1 starting at the top left corner
+    pub fn map_position(&self, translation: Vec2) -> Vec2 {
+        // LDtk world coordinates go down, bevy ones go up
+        let ldtk_position = Vec2::new(translation.x, -translation.y);
+        let size = (self.size.as_ivec2() * self.grid_size).as_vec2();
+        (ldtk_position - self.origin.as_vec2()) / size
+    }
+}
+
+const MINIMAP_WIDTH: f32 = 160.;
+const MINIMAP_HEIGHT: f32 = 120.;
+/// Screen pixels per cell on the minimap
+const MINIMAP_CELL_PIXELS: f32 = 3.;
+/// Part of the window the world map may cover
+const WORLD_MAP_COVERAGE: f32 = 0.9;
+const MARKER_SIZE: f32 = 6.;
+
+const MARKER_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+const FLOOR_COLOR: [u8; 4] = [140, 128, 102, 230];
+const CURRENT_FLOOR_COLOR: [u8; 4] = [196, 180, 140, 240];
+const WALL_COLOR: [u8; 4] = [40, 32, 26, 255];
+const UNVISITED_FLOOR_COLOR: [u8; 4] = [64, 64, 64, 150];
+const UNVISITED_WALL_COLOR: [u8; 4] = [24, 24, 24, 150];
+
+#[derive(Component)]
+struct Minimap;
+
+#[derive(Component)]
+struct WorldMapScreen;
+
+/// Node showing the `WorldMap` image
+#[derive(Component)]
+struct MapImage;
+
+#[derive(Component)]
+struct WorldMapMarker;
+
+/// Paints the cells of a level into the map image, `is_wall` gets cells from the top left corner
+fn draw_level(
+    data: &mut [u8],
+    map_size: UVec2,
+    offset: IVec2,
+    level_size: IVec2,
+    visited: bool,
+    current: bool,
+    is_wall: impl Fn(IVec2) -> bool,
+) {
+    for y in 0..level_size.y {
+        for x in 0..level_size.x {
+            let cell = offset + IVec2::new(x, y);
+            if cell.x < 0
+                || cell.y < 0
+                || cell.x >= map_size.x as i32
+                || cell.y >= map_size.y as i32
+            {
+                continue;
+            }
+
+            let color = match (visited, is_wall(IVec2::new(x, y))) {
+                (true, true) => WALL_COLOR,
+                (true, false) if current => CURRENT_FLOOR_COLOR,
+                (true, false) => FLOOR_COLOR,
+                (false, true) => UNVISITED_WALL_COLOR,
+                (false, false) => UNVISITED_FLOOR_COLOR,
+            };
+
+            let pixel = ((cell.y as u32 * map_size.x + cell.x as u32) * 4) as usize;
+            data[pixel..pixel + 4].copy_from_slice(&color);
+        }
+    }
+}
+
+/// Whether a cell counted from the top left corner, like the map does, is in `walls`
+fn is_level_wall(walls: &LevelWalls, cell: IVec2) -> bool {
+    walls.cells.contains(&GridCoords {
+        x: cell.x,
+        y: walls.height - 1 - cell.y,
+    })
+}
+
+impl WorldMapPlugin {
+    fn track_visited_levels(
+        mut entered_events: EventReader<LevelEntered>,
+        mut visited: ResMut<VisitedLevels>,
+    ) {
+        for event in entered_events.iter() {
+            if !visited.0.contains(&event.info.iid) {
+                visited.0.insert(event.info.iid.clone());
+            }
+        }
+    }
+
+    /// Redraws the map when the project is (re)loaded or the player enters a level
+    fn rebuild_map_image(
+        mut asset_events: EventReader<AssetEvent<LdtkAsset>>,
+        visited: Res<VisitedLevels>,
+        level_info: Res<LevelInfo>,
+        wall_settings: Res<WallSettings>,
+        world_query: Query<&Handle<LdtkAsset>>,
+        ldtk_assets: Res<Assets<LdtkAsset>>,
+        level_query: Query<(&Handle<LdtkLevel>, &LevelWalls)>,
+        ldtk_levels: Res<Assets<LdtkLevel>>,
+        mut images: ResMut<Assets<Image>>,
+        mut world_map: ResMut<WorldMap>,
+        mut image_query: Query<&mut UiImage, With<MapImage>>,
+    ) {
+        let project_changed = asset_events.iter().count() > 0;
+        if !project_changed && !visited.is_changed() && !level_info.is_changed() {
+            return;
+        }
+
+        let ldtk_asset = match world_query
+            .iter()
+            .find_map(|handle| ldtk_assets.get(handle))
+        {
+            Some(ldtk_asset) => ldtk_asset,
+            None => return,
+        };
+
+        let levels = &ldtk_asset.project.levels;
+        let wall_layers: Vec<_> = levels
+            .iter()
+            .filter_map(|level| {
+                let layer = level
+                    .layer_instances
+                    .iter()
+                    .flatten()
+                    .find(|layer| layer.identifier == wall_settings.layer_identifier)?;
+                Some((level, layer))
+            })
+            .collect();
+
+        let grid_size = match wall_layers.first() {
+            Some((_, layer)) => layer.grid_size,
+            None => return,
+        };
+
+        let min = levels
+            .iter()
+            .map(|level| IVec2::new(level.world_x, level.world_y))
+            .reduce(IVec2::min)
+            .unwrap_or_default();
+        let max = levels
+            .iter()
+            .map(|level| IVec2::new(level.world_x + level.px_wid, level.world_y + level.px_hei))
+            .reduce(IVec2::max)
+            .unwrap_or_default();
+
+        let size = ((max - min) / grid_size).max(IVec2::ONE).as_uvec2();
+        let mut data = vec![0; (size.x * size.y * 4) as usize];
+
+        // the walls of spawned levels may have been edited since they were loaded
+        let spawned_walls: HashMap<&str, &LevelWalls> = level_query
+            .iter()
+            .filter_map(|(handle, walls)| {
+                Some((ldtk_levels.get(handle)?.level.iid.as_str(), walls))
+            })
+            .collect();
+
+        for (level, layer) in wall_layers {
+            let offset = (IVec2::new(level.world_x, level.world_y) - min) / grid_size;
+            let spawned = spawned_walls.get(level.iid.as_str());
+
+            draw_level(
+                &mut data,
+                size,
+                offset,
+                IVec2::new(layer.c_wid, layer.c_hei),
+                visited.0.contains(&level.iid),
+                level.iid == level_info.iid,
+                |cell| match spawned {
+                    Some(walls) => is_level_wall(walls, cell),
+                    None => {
+                        let index = (cell.y * layer.c_wid + cell.x) as usize;
+                        layer.int_grid_csv.get(index) == Some(&WALL_INT_GRID_VALUE)
+                    }
+                },
+            );
+        }
+
+        let image = Image::new(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        );
+
+        // later rebuilds replace the first image, so the map nodes keep showing the same handle
+        let first_image = world_map.image == Handle::default();
+        let image = if first_image {
+            images.add(image)
+        } else {
+            images.set(world_map.image.id, image)
+        };
+
+        *world_map = WorldMap {
+            image,
+            origin: min,
+            size,
+            grid_size,
+        };
+
+        if first_image {
+            for mut ui_image in image_query.iter_mut() {
+                ui_image.0 = world_map.image.clone();
+            }
+        }
+    }
+
+    /// Redraws the levels whose walls were changed with `WallEdit`s
+    fn redraw_edited_walls(
+        world_map: Res<WorldMap>,
+        visited: Res<VisitedLevels>,
+        level_info: Res<LevelInfo>,
+        ldtk_levels: Res<Assets<LdtkLevel>>,
+        mut images: ResMut<Assets<Image>>,
+        level_query: Query<(&Handle<LdtkLevel>, &LevelWalls), Changed<LevelWalls>>,
+    ) {
+        // the image is only touched for actual edits, every touch uploads it again
+        if level_query.is_empty() {
+            return;
+        }
+
+        let image = match images.get_mut(&world_map.image) {
+            Some(image) => image,
+            None => return,
+        };
+
+        for (handle, walls) in level_query.iter() {
+            let level = match ldtk_levels.get(handle) {
+                Some(ldtk_level) => &ldtk_level.level,
+                None => continue,
+            };
+
+            let offset =
+                (IVec2::new(level.world_x, level.world_y) - world_map.origin) / world_map.grid_size;
+
+            draw_level(
+                &mut image.data,
+                world_map.size,
+                offset,
+                IVec2::new(walls.width, walls.height),
+                visited.0.contains(&level.iid),
+                level.iid == level_info.iid,
+                |cell| is_level_wall(walls, cell),
+            );
+        }
+    }
+
+    fn spawn_map_ui(mut commands: Commands, world_map: Res<WorldMap>) {
+        let marker = || NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Px(MARKER_SIZE), Val::Px(MARKER_SIZE)),
+                margin: Rect {
+                    left: Val::Px(-MARKER_SIZE / 2.),
+                    top: Val::Px(-MARKER_SIZE / 2.),
+                    ..default()
+                },
+                ..default()
+            },
+            color: MARKER_COLOR.into(),
+            ..default()
+        };
+
+        let map_image = || ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            image: UiImage(world_map.image.clone()),
+            ..default()
+        };
+
+        // the minimap image moves under a marker fixed in the middle
+        commands
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        right: Val::Px(8.),
+                        top: Val::Px(8.),
+                        ..default()
+                    },
+                    size: Size::new(Val::Px(MINIMAP_WIDTH), Val::Px(MINIMAP_HEIGHT)),
+                    overflow: Overflow::Hidden,
+                    ..default()
+                },
+                color: Color::rgba(0., 0., 0., 0.5).into(),
+                ..default()
+            })
+            .insert(Name::new("Minimap"))
+            .insert(Minimap)
+            // hovered maps keep clicks from walking the player
+            .insert(Interaction::default())
+            .with_children(|parent| {
+                parent.spawn_bundle(map_image()).insert(MapImage);
+                parent.spawn_bundle(NodeBundle {
+                    style: Style {
+                        position: Rect {
+                            left: Val::Px(MINIMAP_WIDTH / 2.),
+                            top: Val::Px(MINIMAP_HEIGHT / 2.),
+                            ..default()
+                        },
+                        ..marker().style
+                    },
+                    ..marker()
+                });
+            });
+
+        // the world map marker moves over a fixed image
+        commands
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        left: Val::Px(0.),
+                        top: Val::Px(0.),
+                        ..default()
+                    },
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    display: Display::None,
+                    ..default()
+                },
+                color: Color::rgba(0., 0., 0., 0.75).into(),
+                ..default()
+            })
+            .insert(Name::new("WorldMap"))
+            .insert(WorldMapScreen)
+            .insert(Interaction::default())
+            .with_children(|parent| {
+                parent
+                    .spawn_bundle(ImageBundle {
+                        style: Style::default(),
+                        ..map_image()
+                    })
+                    .insert(MapImage)
+                    .with_children(|parent| {
+                        parent.spawn_bundle(marker()).insert(WorldMapMarker);
+                    });
+            });
+    }
+
+    fn toggle_world_map(
+        actions: Res<Actions>,
+        mut screen_query: Query<&mut Style, (With<WorldMapScreen>, Without<Minimap>)>,
+        mut minimap_query: Query<&mut Style, (With<Minimap>, Without<WorldMapScreen>)>,
+    ) {
+        if !actions.just_pressed(Action::ToggleWorldMap) {
+            return;
+        }
+
+        for mut screen in screen_query.iter_mut() {
+            let shown = screen.display == Display::None;
+            screen.display = if shown { Display::Flex } else { Display::None };
+
+            for mut minimap in minimap_query.iter_mut() {
+                minimap.display = if shown { Display::None } else { Display::Flex };
+            }
+        }
+    }
+
+    fn update_minimap(
+        world_map: Res<WorldMap>,
+        player_query: Query<&GlobalTransform, With<Player>>,
+        minimap_query: Query<&Children, With<Minimap>>,
+        mut image_query: Query<&mut Style, With<MapImage>>,
+    ) {
+        if world_map.size == UVec2::ZERO {
+            return;
+        }
+
+        let player = match player_query.get_single() {
+            Ok(transform) => world_map.map_position(transform.translation.truncate()),
+            Err(_) => return,
+        };
+
+        let image_size = world_map.size.as_vec2() * MINIMAP_CELL_PIXELS;
+        for children in minimap_query.iter() {
+            for &child in children.iter() {
+                if let Ok(mut style) = image_query.get_mut(child) {
+                    let position =
+                        Vec2::new(MINIMAP_WIDTH, MINIMAP_HEIGHT) / 2. - player * image_size;
+                    style.size = Size::new(Val::Px(image_size.x), Val::Px(image_size.y));
+                    style.position = Rect {
+                        left: Val::Px(position.x),
+                        top: Val::Px(position.y),
+                        ..default()
+                    };
+                }
+            }
+        }
+    }
+
+    fn update_world_map(
+        windows: Res<Windows>,
+        world_map: Res<WorldMap>,
+        player_query: Query<&GlobalTransform, With<Player>>,
+        screen_query: Query<&Children, With<WorldMapScreen>>,
+        mut image_query: Query<&mut Style, (With<MapImage>, Without<WorldMapMarker>)>,
+        mut marker_query: Query<&mut Style, (With<WorldMapMarker>, Without<MapImage>)>,
+    ) {
+        if world_map.size == UVec2::ZERO {
+            return;
+        }
+
+        let window = match windows.get_primary() {
+            Some(window) => window,
+            None => return,
+        };
+
+        // whole screen pixels per cell, so the map stays crisp
+        let cells = world_map.size.as_vec2();
+        let scale = (Vec2::new(window.width(), window.height()) * WORLD_MAP_COVERAGE / cells)
+            .min_element()
+            .floor()
+            .max(1.);
+
+        for children in screen_query.iter() {
+            for &child in children.iter() {
+                if let Ok(mut style) = image_query.get_mut(child) {
+                    style.size = Size::new(Val::Px(cells.x * scale), Val::Px(cells.y * scale));
+                }
+            }
+        }
+
+        if let Ok(transform) = player_query.get_single() {
+            let player = world_map.map_position(transform.translation.truncate());
+            for mut style in marker_query.iter_mut() {
+                style.position = Rect {
+                    left: Val::Percent(player.x * 100.),
+                    top: Val::Percent(player.y * 100.),
+                    ..default()
+                };
+            }
+        }
+    }
+}
@@ -1,33 +1,38 @@
 #![allow(clippy::type_complexity)]
 
-use crate::{components::*, plugins::SpriteSort};
-use bevy::{prelude::*, render::camera::ScalingMode};
+use crate::{
+    components::*,
+    plugins::{
+        Action, Actions, CameraEffects, CameraFollow, PathFollow, PendingSpawn, TileSettings,
+        TransitionState,
+    },
+    wall_merging::merge_wall_rects,
+    AppState,
+};
+use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 
 use heron::prelude::*;
 
 #[derive(Component)]
 pub struct MainCamera;
 
-pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+/// Has to run before anything is loaded, assets loaded earlier are not watched
+pub fn watch_for_asset_changes(asset_server: Res<AssetServer>) {
+    asset_server.watch_for_changes().unwrap();
+}
+
+/// The map itself is loaded by `TravelPlugin` from the map registry
+pub fn setup(mut commands: Commands) {
     let camera = OrthographicCameraBundle::new_2d();
     commands
         .spawn_bundle(camera)
         .insert(Name::new("MainCamera"))
-        .insert(MainCamera);
-
-    asset_server.watch_for_changes().unwrap();
-
-    let ldtk_handle = asset_server.load("maps/verres-home/map.ldtk");
-    commands
-        .spawn_bundle(LdtkWorldBundle {
-            ldtk_handle,
-            ..Default::default()
-        })
-        .insert(Name::new("Map"))
-        .insert(SpriteSort::default());
+        .insert(MainCamera)
+        .insert(CameraFollow::default())
+        .insert(CameraEffects::default());
 }
 
 pub fn pause_physics_during_load(
@@ -43,52 +48,42 @@ pub fn pause_physics_during_load(
     }
 }
 
-fn calc_movement_input(keyboard: &Res<Input<KeyCode>>) -> Vec2 {
-    let mut input = Vec2::ZERO;
-
-    let left = keyboard.pressed(KeyCode::A) || keyboard.pressed(KeyCode::Left);
-    let right = keyboard.pressed(KeyCode::D) || keyboard.pressed(KeyCode::Right);
-
-    let up = keyboard.pressed(KeyCode::W) || keyboard.pressed(KeyCode::Up);
-    let down = keyboard.pressed(KeyCode::S) || keyboard.pressed(KeyCode::Down);
+/// Pauses the game, and physics with it, or resumes it
+pub fn toggle_pause(
+    actions: Res<Actions>,
+    mut state: ResMut<State<AppState>>,
+    mut physics_time: ResMut<PhysicsTime>,
+) {
+    if !actions.just_pressed(Action::Pause) {
+        return;
+    }
 
-    input.x = (right as i32 - left as i32) as f32;
-    input.y = (up as i32 - down as i32) as f32;
+    let result = match state.current() {
+        AppState::InGame => {
+            physics_time.set_scale(0.);
+            state.push(AppState::Paused)
+        }
+        AppState::Paused => {
+            physics_time.set_scale(1.);
+            state.pop()
+        }
+        _ => return,
+    };
 
-    input
+    if let Err(error) = result {
+        warn!("Can't toggle pause: {:?}", error);
+    }
 }
 
-/// Spawns heron collisions for the walls of a level
-///
-/// You could just insert a ColliderBundle in to the WallBundle,
-/// but this spawns a different collider for EVERY wall tile.
-/// This approach leads to bad performance.
-///
-/// Instead, by flagging the wall tiles and spawning the collisions later,
-/// we can minimize the amount of colliding entities.
-///
-/// The algorithm used here is a nice compromise between simplicity, speed,
-/// and a small number of rectangle colliders.
-/// In basic terms, it will:
-/// 1. consider where the walls are
-/// 2. combine wall tiles into flat "plates" in each individual row
-/// 3. combine the plates into rectangles across multiple rows wherever possible
-/// 4. spawn colliders for each rectangle
-pub fn spawn_wall_collision(
+/// Gathers newly spawned wall tiles into the `LevelWalls` of their levels
+pub fn collect_walls(
     mut commands: Commands,
     wall_query: Query<(&GridCoords, &Parent), Added<Wall>>,
     parent_query: Query<&Parent, Without<Wall>>,
-    level_query: Query<(Entity, &Handle<LdtkLevel>)>,
+    mut level_query: Query<(&Handle<LdtkLevel>, Option<&mut LevelWalls>)>,
     levels: Res<Assets<LdtkLevel>>,
+    settings: Res<WallSettings>,
 ) {
-    /// Represents a wide wall that is 1 tile tall
-    /// Used to spawn wall collisions
-    #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
-    struct Plate {
-        left: i32,
-        right: i32,
-    }
-
     // consider where the walls are
     // storing them as GridCoords in a HashSet for quick, easy lookup
     let mut level_to_wall_locations: HashMap<Entity, HashSet<GridCoords>> = HashMap::new();
@@ -104,181 +99,154 @@ pub fn spawn_wall_collision(
         }
     });
 
-    if !wall_query.is_empty() {
-        level_query.for_each(|(level_entity, level_handle)| {
-            if let Some(level_walls) = level_to_wall_locations.get(&level_entity) {
-                let level = levels
-                    .get(level_handle)
-                    .expect("Level should be loaded by this point");
-
-                let LayerInstance {
-                    c_wid: width,
-                    c_hei: height,
-                    grid_size,
-                    ..
-                } = level
-                    .level
-                    .layer_instances
-                    .clone()
-                    .expect("Level asset should have layers")[0];
-
-                // combine wall tiles into flat "plates" in each individual row
-                let mut plate_stack: Vec<Vec<Plate>> = Vec::new();
-
-                for y in 0..height {
-                    let mut row_plates: Vec<Plate> = Vec::new();
-                    let mut plate_start = None;
-
-                    // + 1 to the width so the algorithm "terminates" plates that touch the right
-                    // edge
-                    for x in 0..width + 1 {
-                        match (plate_start, level_walls.contains(&GridCoords { x, y })) {
-                            (Some(s), false) => {
-                                row_plates.push(Plate {
-                                    left: s,
-                                    right: x - 1,
-                                });
-                                plate_start = None;
-                            }
-                            (None, true) => plate_start = Some(x),
-                            _ => (),
-                        }
-                    }
+    for (level_entity, wall_locations) in level_to_wall_locations {
+        if let Ok((level_handle, level_walls)) = level_query.get_mut(level_entity) {
+            if let Some(mut level_walls) = level_walls {
+                level_walls.cells.extend(wall_locations);
+                continue;
+            }
 
-                    plate_stack.push(row_plates);
+            let level = match levels.get(level_handle) {
+                Some(level) => level,
+                None => {
+                    error!(
+                        "Walls of {:?} spawned before its level was loaded",
+                        level_entity
+                    );
+                    continue;
                 }
+            };
+
+            match LevelWalls::for_level(level, &settings.layer_identifier) {
+                Ok(mut level_walls) => {
+                    level_walls.cells = wall_locations;
+                    commands.entity(level_entity).insert(level_walls);
+                }
+                Err(error) => error!("{}", error),
+            }
+        }
+    }
+}
+
+/// Applies `WallEdit` events to the `LevelWalls` of their levels
+pub fn edit_walls(
+    mut commands: Commands,
+    mut wall_edits: EventReader<WallEdit>,
+    mut level_query: Query<(&Handle<LdtkLevel>, Option<&mut LevelWalls>)>,
+    levels: Res<Assets<LdtkLevel>>,
+    settings: Res<WallSettings>,
+) {
+    // levels without a single wall get their LevelWalls here
+    let mut new_level_walls: HashMap<Entity, LevelWalls> = HashMap::new();
+
+    for edit in wall_edits.iter() {
+        let (level_entity, coords, is_wall) = match *edit {
+            WallEdit::Add { level, coords } => (level, coords, true),
+            WallEdit::Remove { level, coords } => (level, coords, false),
+        };
+
+        let (level_handle, level_walls) = match level_query.get_mut(level_entity) {
+            Ok(level) => level,
+            Err(_) => {
+                warn!("Wall edit for {:?}, which is not a level", level_entity);
+                continue;
+            }
+        };
 
-                // combine "plates" into rectangles across multiple rows
-                let mut wall_rects: Vec<Rect<i32>> = Vec::new();
-                let mut previous_rects: HashMap<Plate, Rect<i32>> = HashMap::new();
-
-                // an extra empty row so the algorithm "terminates" the rects that touch the top
-                // edge
-                plate_stack.push(Vec::new());
-
-                for (y, row) in plate_stack.iter().enumerate() {
-                    let mut current_rects: HashMap<Plate, Rect<i32>> = HashMap::new();
-                    for plate in row {
-                        if let Some(previous_rect) = previous_rects.remove(plate) {
-                            current_rects.insert(
-                                *plate,
-                                Rect {
-                                    top: previous_rect.top + 1,
-                                    ..previous_rect
-                                },
-                            );
-                        } else {
-                            current_rects.insert(
-                                *plate,
-                                Rect {
-                                    bottom: y as i32,
-                                    top: y as i32,
-                                    left: plate.left,
-                                    right: plate.right,
-                                },
-                            );
+        if let Some(mut level_walls) = level_walls {
+            if !level_walls.in_level(coords) {
+                warn!("Wall edit at {:?}, outside of {:?}", coords, level_entity);
+                continue;
+            }
+
+            if level_walls.cells.contains(&coords) != is_wall {
+                level_walls.set(coords, is_wall);
+            }
+        } else if let Some(level) = levels.get(level_handle) {
+            let level_walls = match new_level_walls.entry(level_entity) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    match LevelWalls::for_level(level, &settings.layer_identifier) {
+                        Ok(level_walls) => entry.insert(level_walls),
+                        Err(error) => {
+                            error!("{}", error);
+                            continue;
                         }
                     }
-
-                    // Any plates that weren't removed above have terminated
-                    wall_rects.append(&mut previous_rects.values().copied().collect());
-                    previous_rects = current_rects;
                 }
+            };
 
-                // spawn colliders for every rectangle
-                for wall_rect in wall_rects {
-                    commands
-                        .spawn()
-                        .insert(CollisionShape::Cuboid {
-                            half_extends: Vec3::new(
-                                (wall_rect.right as f32 - wall_rect.left as f32 + 1.)
-                                    * grid_size as f32
-                                    / 2.,
-                                (wall_rect.top as f32 - wall_rect.bottom as f32 + 1.)
-                                    * grid_size as f32
-                                    / 2.,
-                                0.,
-                            ),
-                            border_radius: None,
-                        })
-                        .insert(RigidBody::Static)
-                        .insert(PhysicMaterial {
-                            friction: 0.1,
-                            ..Default::default()
-                        })
-                        .insert(Transform::from_xyz(
-                            (wall_rect.left + wall_rect.right + 1) as f32 * grid_size as f32 / 2.,
-                            (wall_rect.bottom + wall_rect.top + 1) as f32 * grid_size as f32 / 2.,
-                            0.,
-                        ))
-                        .insert(GlobalTransform::default())
-                        // Making the collider a child of the level serves two purposes:
-                        // 1. Adjusts the transforms to be relative to the level for free
-                        // 2. the colliders will be despawned automatically when levels unload
-                        .insert(Parent(level_entity));
-                }
+            if !level_walls.in_level(coords) {
+                warn!("Wall edit at {:?}, outside of {:?}", coords, level_entity);
+                continue;
             }
-        });
+
+            level_walls.set(coords, is_wall);
+        }
     }
-}
 
-const ASPECT_RATIO: f32 = 16. / 9.;
+    for (level_entity, level_walls) in new_level_walls {
+        commands.entity(level_entity).insert(level_walls);
+    }
+}
 
-pub fn camera_fit_inside_current_level(
-    mut camera_query: Query<
-        (&mut OrthographicProjection, &mut Transform),
-        (Without<Player>, With<MainCamera>),
-    >,
-    player_query: Query<&Transform, With<Player>>,
-    level_query: Query<
-        (&Transform, &Handle<LdtkLevel>),
-        (Without<OrthographicProjection>, Without<Player>),
-    >,
-    level_selection: Res<LevelSelection>,
-    ldtk_levels: Res<Assets<LdtkLevel>>,
+/// Spawns heron collisions for the walls of a level
+///
+/// You could just insert a ColliderBundle in to the WallBundle,
+/// but this spawns a different collider for EVERY wall tile.
+/// This approach leads to bad performance.
+///
+/// Instead, by flagging the wall tiles and spawning the collisions later,
+/// we can minimize the amount of colliding entities.
+///
+/// Whenever the `LevelWalls` of a level change, only the colliders of that level are rebuilt.
+pub fn spawn_wall_collision(
+    mut commands: Commands,
+    level_query: Query<(Entity, &LevelWalls), Changed<LevelWalls>>,
+    collider_query: Query<(Entity, &Parent), With<WallCollider>>,
 ) {
-    if let Ok(Transform {
-        translation: player_translation,
-        ..
-    }) = player_query.get_single()
-    {
-        let player_translation = *player_translation;
-
-        let (mut orthographic_projection, mut camera_transform) = camera_query.single_mut();
-        for (level_transform, level_handle) in level_query.iter() {
-            if let Some(ldtk_level) = ldtk_levels.get(level_handle) {
-                let level = &ldtk_level.level;
-                if level_selection.is_match(&0, level) {
-                    let level_ratio = level.px_wid as f32 / ldtk_level.level.px_hei as f32;
-
-                    orthographic_projection.scaling_mode = ScalingMode::None;
-                    orthographic_projection.bottom = 0.;
-                    orthographic_projection.left = 0.;
-                    if level_ratio > ASPECT_RATIO {
-                        // level is wider than the screen
-                        orthographic_projection.top = (level.px_hei as f32 / 9.).round() * 9.;
-                        orthographic_projection.right = orthographic_projection.top * ASPECT_RATIO;
-                        camera_transform.translation.x = (player_translation.x
-                            - level_transform.translation.x
-                            - orthographic_projection.right / 2.)
-                            .clamp(0., level.px_wid as f32 - orthographic_projection.right);
-                        camera_transform.translation.y = 0.;
-                    } else {
-                        // level is taller than the screen
-                        orthographic_projection.right = (level.px_wid as f32 / 16.).round() * 16.;
-                        orthographic_projection.top = orthographic_projection.right / ASPECT_RATIO;
-                        camera_transform.translation.y = (player_translation.y
-                            - level_transform.translation.y
-                            - orthographic_projection.top / 2.)
-                            .clamp(0., level.px_hei as f32 - orthographic_projection.top);
-                        camera_transform.translation.x = 0.;
-                    }
-
-                    camera_transform.translation.x += level_transform.translation.x;
-                    camera_transform.translation.y += level_transform.translation.y;
-                }
+    for (level_entity, level_walls) in level_query.iter() {
+        for (collider, &Parent(parent)) in collider_query.iter() {
+            if parent == level_entity {
+                commands.entity(collider).despawn_recursive();
             }
         }
+
+        let grid_size = level_walls.grid_size;
+
+        // spawn colliders for every rectangle
+        for wall_rect in merge_wall_rects(&level_walls.cells, level_walls.width, level_walls.height)
+        {
+            commands
+                .spawn()
+                .insert(CollisionShape::Cuboid {
+                    half_extends: Vec3::new(
+                        (wall_rect.right as f32 - wall_rect.left as f32 + 1.) * grid_size as f32
+                            / 2.,
+                        (wall_rect.top as f32 - wall_rect.bottom as f32 + 1.) * grid_size as f32
+                            / 2.,
+                        0.,
+                    ),
+                    border_radius: None,
+                })
+                .insert(RigidBody::Static)
+                .insert(CollisionLayer::Wall.collision_layers())
+                .insert(PhysicMaterial {
+                    friction: 0.1,
+                    ..Default::default()
+                })
+                .insert(Transform::from_xyz(
+                    (wall_rect.left + wall_rect.right + 1) as f32 * grid_size as f32 / 2.,
+                    (wall_rect.bottom + wall_rect.top + 1) as f32 * grid_size as f32 / 2.,
+                    0.,
+                ))
+                .insert(GlobalTransform::default())
+                .insert(WallCollider)
+                // Making the collider a child of the level serves two purposes:
+                // 1. Adjusts the transforms to be relative to the level for free
+                // 2. the colliders will be despawned automatically when levels unload
+                .insert(Parent(level_entity));
+        }
     }
 }
 
@@ -287,7 +255,13 @@ pub fn update_level_selection(
     player_query: Query<&Transform, With<Player>>,
     mut level_selection: ResMut<LevelSelection>,
     ldtk_levels: Res<Assets<LdtkLevel>>,
+    pending_spawn: Option<Res<PendingSpawn>>,
 ) {
+    // the player is still where it was before traveling
+    if pending_spawn.is_some() {
+        return;
+    }
+
     for (handle, level) in level_query.iter() {
         if let Some(ldtk_level) = ldtk_levels.get(handle) {
             let level_bounds = Rect {
@@ -311,24 +285,66 @@ pub fn update_level_selection(
     }
 }
 
-pub fn player_movement(mut query: Query<(&mut Velocity, &MovementController)>) {
-    for (mut velocity, movement) in query.iter_mut() {
-        let input = movement.0.normalize_or_zero() * 96.;
+/// Speeds the velocity up towards the movement input, or slows it down without input
+pub fn player_movement(
+    time: Res<Time>,
+    tile_settings: Res<TileSettings>,
+    mut query: Query<(
+        &mut Velocity,
+        &MovementController,
+        &MovementTuning,
+        Option<&Stamina>,
+        Option<&TileContacts>,
+    )>,
+) {
+    for (mut velocity, movement, tuning, stamina, tile_contacts) in query.iter_mut() {
+        let mut speed = tuning.max_speed;
+        if stamina.map_or(false, |stamina| stamina.sprinting) {
+            speed *= tuning.sprint_multiplier;
+        }
+        if tile_contacts.map_or(false, |contacts| contacts.touches(TileBehavior::Slow)) {
+            speed *= tile_settings.slow_factor;
+        }
+
+        // analog input keeps its magnitude, diagonals aren't faster
+        let target = movement.0.clamp_length_max(1.) * speed;
+
+        let rate = if target == Vec2::ZERO {
+            tuning.friction
+        } else {
+            tuning.acceleration
+        };
+
+        let current = velocity.linear.truncate();
+        let difference = target - current;
+        let step = rate * time.delta_seconds();
+
+        let linear = if difference.length() <= step {
+            target
+        } else {
+            current + difference.normalize() * step
+        };
 
-        velocity.linear.x = input.x;
-        velocity.linear.y = input.y;
+        velocity.linear.x = linear.x;
+        velocity.linear.y = linear.y;
     }
 }
 
 #[derive(Clone, Default, Component)]
-pub struct MovementController(Vec2);
+pub struct MovementController(pub Vec2);
 
+/// Players walking a clicked path are steered by `ClickToMovePlugin` instead
 pub fn read_player_input(
-    keyboard: Res<Input<KeyCode>>,
-    mut query: Query<(&mut MovementController,), With<Player>>,
+    actions: Res<Actions>,
+    transition: Res<TransitionState>,
+    mut query: Query<(&mut MovementController,), (With<Player>, Without<PathFollow>)>,
 ) {
     for (mut movement,) in query.iter_mut() {
-        movement.0 = calc_movement_input(&keyboard);
+        movement.0 = if transition.is_active() {
+            Vec2::ZERO
+        } else {
+            actions.movement()
+        };
     }
 }
 
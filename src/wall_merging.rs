@@ -0,0 +1,263 @@
+//! Merging of wall tiles into rectangle colliders
+//!
+//! Kept free of ECS types, so it can be tested and benchmarked on its own
+//! (`benches/wall_merging.rs` includes this file directly).
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::Rect;
+use bevy_ecs_ldtk::prelude::GridCoords;
+
+/// Merges wall cells into as few rectangles as reasonably possible
+///
+/// The algorithm used here is a nice compromise between simplicity, speed,
+/// and a small number of rectangle colliders.
+/// In basic terms, it will:
+/// 1. consider where the walls are
+/// 2. combine wall tiles into flat "plates" in each individual row
+/// 3. combine the plates into rectangles across multiple rows wherever possible
+///
+/// Rectangles are inclusive on all sides, in grid coordinates of a `width` x `height` level.
+/// Cells outside of the level are ignored.
+pub fn merge_wall_rects(walls: &HashSet<GridCoords>, width: i32, height: i32) -> Vec<Rect<i32>> {
+    /// Represents a wide wall that is 1 tile tall
+    /// Used to spawn wall collisions
+    #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+    struct Plate {
+        left: i32,
+        right: i32,
+    }
+
+    // combine wall tiles into flat "plates" in each individual row
+    let mut plate_stack: Vec<Vec<Plate>> = Vec::new();
+
+    for y in 0..height {
+        let mut row_plates: Vec<Plate> = Vec::new();
+        let mut plate_start = None;
+
+        // + 1 to the width so the algorithm "terminates" plates that touch the right
+        // edge, that column counts as empty even if `walls` has cells in it
+        for x in 0..width + 1 {
+            let is_wall = x < width && walls.contains(&GridCoords { x, y });
+            match (plate_start, is_wall) {
+                (Some(s), false) => {
+                    row_plates.push(Plate {
+                        left: s,
+                        right: x - 1,
+                    });
+                    plate_start = None;
+                }
+                (None, true) => plate_start = Some(x),
+                _ => (),
+            }
+        }
+
+        plate_stack.push(row_plates);
+    }
+
+    // combine "plates" into rectangles across multiple rows
+    let mut wall_rects: Vec<Rect<i32>> = Vec::new();
+    let mut previous_rects: HashMap<Plate, Rect<i32>> = HashMap::new();
+
+    // an extra empty row so the algorithm "terminates" the rects that touch the top
+    // edge
+    plate_stack.push(Vec::new());
+
+    for (y, row) in plate_stack.iter().enumerate() {
+        let mut current_rects: HashMap<Plate, Rect<i32>> = HashMap::new();
+        for plate in row {
+            if let Some(previous_rect) = previous_rects.remove(plate) {
+                current_rects.insert(
+                    *plate,
+                    Rect {
+                        top: previous_rect.top + 1,
+                        ..previous_rect
+                    },
+                );
+            } else {
+                current_rects.insert(
+                    *plate,
+                    Rect {
+                        bottom: y as i32,
+                        top: y as i32,
+                        left: plate.left,
+                        right: plate.right,
+                    },
+                );
+            }
+        }
+
+        // Any plates that weren't removed above have terminated
+        wall_rects.append(&mut previous_rects.values().copied().collect());
+        previous_rects = current_rects;
+    }
+
+    wall_rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    fn walls(cells: &[(i32, i32)]) -> HashSet<GridCoords> {
+        cells.iter().map(|&(x, y)| GridCoords { x, y }).collect()
+    }
+
+    fn cells(rect: &Rect<i32>) -> impl Iterator<Item = GridCoords> + '_ {
+        (rect.bottom..=rect.top)
+            .flat_map(move |y| (rect.left..=rect.right).map(move |x| GridCoords { x, y }))
+    }
+
+    /// Panics unless the rects cover exactly the walls, each cell only once
+    fn assert_exact_cover(walls: &HashSet<GridCoords>, rects: &[Rect<i32>]) {
+        let mut covered = HashSet::new();
+
+        for rect in rects {
+            assert!(
+                rect.left <= rect.right && rect.bottom <= rect.top,
+                "{:?}",
+                rect
+            );
+
+            for cell in cells(rect) {
+                assert!(
+                    walls.contains(&cell),
+                    "{:?} covers {:?}, not a wall",
+                    rect,
+                    cell
+                );
+                assert!(covered.insert(cell), "{:?} is covered twice", cell);
+            }
+        }
+
+        assert_eq!(&covered, walls);
+    }
+
+    #[test]
+    fn no_walls() {
+        assert!(merge_wall_rects(&HashSet::new(), 8, 8).is_empty());
+    }
+
+    #[test]
+    fn single_cell() {
+        let walls = walls(&[(3, 4)]);
+        let rects = merge_wall_rects(&walls, 8, 8);
+
+        assert_eq!(rects.len(), 1);
+        assert_exact_cover(&walls, &rects);
+    }
+
+    #[test]
+    fn full_level_is_one_rect() {
+        let walls: HashSet<_> = (0..6)
+            .flat_map(|y| (0..5).map(move |x| GridCoords { x, y }))
+            .collect();
+        let rects = merge_wall_rects(&walls, 5, 6);
+
+        assert_eq!(
+            rects,
+            vec![Rect {
+                left: 0,
+                right: 4,
+                bottom: 0,
+                top: 5,
+            }]
+        );
+    }
+
+    #[test]
+    fn plates_touching_the_edges() {
+        let walls = walls(&[(0, 0), (1, 0), (6, 7), (7, 7)]);
+        let rects = merge_wall_rects(&walls, 8, 8);
+
+        assert_eq!(rects.len(), 2);
+        assert_exact_cover(&walls, &rects);
+    }
+
+    #[test]
+    fn plates_of_different_width_are_not_merged() {
+        // ##
+        // #
+        let walls = walls(&[(0, 0), (0, 1), (1, 1)]);
+        let rects = merge_wall_rects(&walls, 4, 4);
+
+        assert_eq!(rects.len(), 2);
+        assert_exact_cover(&walls, &rects);
+    }
+
+    #[test]
+    fn equal_plates_merge_across_rows() {
+        let walls = walls(&[(1, 1), (2, 1), (1, 2), (2, 2), (1, 3), (2, 3)]);
+        let rects = merge_wall_rects(&walls, 4, 4);
+
+        assert_eq!(
+            rects,
+            vec![Rect {
+                left: 1,
+                right: 2,
+                bottom: 1,
+                top: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn cells_outside_of_the_level_are_ignored() {
+        let rects = merge_wall_rects(&walls(&[(-1, 0), (4, 0), (0, 4)]), 4, 4);
+
+        assert!(rects.is_empty());
+    }
+
+    #[test]
+    fn cells_past_the_right_edge_dont_swallow_edge_plates() {
+        let rects = merge_wall_rects(&walls(&[(2, 1), (3, 1), (4, 1)]), 4, 4);
+
+        assert_exact_cover(&walls(&[(2, 1), (3, 1)]), &rects);
+    }
+
+    /// Walls of the level, leaving out the cells outside of it
+    fn inside(walls: &HashSet<GridCoords>, width: i32, height: i32) -> HashSet<GridCoords> {
+        walls
+            .iter()
+            .filter(|cell| cell.x >= 0 && cell.y >= 0 && cell.x < width && cell.y < height)
+            .copied()
+            .collect()
+    }
+
+    fn level_with_walls() -> impl Strategy<Value = (i32, i32, HashSet<GridCoords>)> {
+        (1..24i32, 1..24i32).prop_flat_map(|(width, height)| {
+            let cells = proptest::collection::hash_set(
+                // a margin of cells outside of the level, which have to be ignored
+                (-2..width + 2, -2..height + 2).prop_map(|(x, y)| GridCoords { x, y }),
+                0..((width + 4) * (height + 4)) as usize,
+            );
+            (Just(width), Just(height), cells)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn rects_cover_exactly_the_walls((width, height, walls) in level_with_walls()) {
+            let rects = merge_wall_rects(&walls, width, height);
+            assert_exact_cover(&inside(&walls, width, height), &rects);
+        }
+
+        #[test]
+        fn never_more_rects_than_plates((width, height, walls) in level_with_walls()) {
+            let plates = (0..height)
+                .map(|y| {
+                    (0..width)
+                        .filter(|&x| {
+                            walls.contains(&GridCoords { x, y })
+                                && (x == 0 || !walls.contains(&GridCoords { x: x - 1, y }))
+                        })
+                        .count()
+                })
+                .sum::<usize>();
+
+            prop_assert!(merge_wall_rects(&walls, width, height).len() <= plates);
+        }
+    }
+}